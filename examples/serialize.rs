@@ -19,4 +19,8 @@ fn main() {
     let json = serde_json::to_string(&tree).unwrap();
 
     println!("{}", json);
+
+    let tree: Tree<i32> = serde_json::from_str(&json).unwrap();
+
+    println!("{:?}", tree.iter().collect::<Vec<_>>());
 }
@@ -0,0 +1,457 @@
+//! Compact binary encoding of a tree.
+//!
+//! The format starts with a header with the magic bytes `FRST` and the version, followed by the
+//! number of roots. Then the nodes are written in depth-first order, each with its number of
+//! children and its encoded value prefixed by the length. All the numbers are LEB128 varints.
+//! Last there is the CRC-32 checksum of all the previous bytes, in little endian.
+
+use std::io::{self, Read, Write};
+
+use crate::{node::Node, tree::Tree};
+
+const MAGIC: &[u8; 4] = b"FRST";
+const VERSION: u8 = 1;
+
+impl<T> Tree<T> {
+    /// Writes the tree in the binary format, encoding each value with `encode`.
+    ///
+    /// The nodes are written with many small writes, so the writer should be buffered.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the writer or the encoding of a value fail.
+    pub fn write_to<W, F>(&self, writer: &mut W, mut encode: F) -> io::Result<()>
+    where
+        W: Write,
+        F: FnMut(&T, &mut Vec<u8>) -> io::Result<()>,
+    {
+        let mut checksum = ChecksumWriter {
+            inner: writer,
+            crc: Crc32::new(),
+        };
+
+        checksum.write_all(MAGIC)?;
+        checksum.write_all(&[VERSION])?;
+
+        let roots = self.roots_count();
+        write_varint(&mut checksum, roots)?;
+
+        let mut buf = Vec::new();
+
+        let mut root = self.first_node;
+        while let Some(root_index) = root {
+            let mut current = Some(root_index);
+            while let Some(index) = current {
+                let node = self.nodes[index].unwrap_ref();
+
+                write_varint(&mut checksum, self.children_count(node))?;
+
+                buf.clear();
+                encode(&node.value, &mut buf)?;
+                write_varint(&mut checksum, buf.len())?;
+                checksum.write_all(&buf)?;
+
+                current = self.next_in_subtree(root_index, index);
+            }
+
+            root = self.nodes[root_index].unwrap_ref().next_sibling;
+        }
+
+        let crc = checksum.crc.finish();
+        writer.write_all(&crc.to_le_bytes())
+    }
+
+    /// Reads a tree in the binary format, decoding each value with `decode`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the header or the checksum are invalid, with
+    /// [`io::ErrorKind::UnexpectedEof`] if the data is truncated, and if the reader or the decoding
+    /// of a value fail.
+    pub fn read_from<R, F>(reader: &mut R, mut decode: F) -> io::Result<Self>
+    where
+        R: Read,
+        F: FnMut(&[u8]) -> io::Result<T>,
+    {
+        let mut checksum = ChecksumReader {
+            inner: reader,
+            crc: Crc32::new(),
+        };
+
+        let mut magic = [0; 4];
+        checksum.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("invalid magic bytes"));
+        }
+
+        let mut version = [0];
+        checksum.read_exact(&mut version)?;
+        if version[0] != VERSION {
+            return Err(invalid_data("unsupported version"));
+        }
+
+        let mut tree = Tree::new();
+
+        let mut roots = read_varint(&mut checksum)?;
+        let mut last_root = None;
+        // Parents with the number of children still to read
+        let mut parents: Vec<(usize, usize)> = Vec::new();
+
+        let mut buf = Vec::new();
+
+        loop {
+            while let Some((_, 0)) = parents.last() {
+                parents.pop();
+            }
+
+            if parents.is_empty() && roots == 0 {
+                break;
+            }
+
+            let children = read_varint(&mut checksum)?;
+
+            let len = read_varint(&mut checksum)?;
+            buf.clear();
+            // Read without allocating the length upfront, since it could be corrupted
+            let read = (&mut checksum).take(len as u64).read_to_end(&mut buf)?;
+            if read != len {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+
+            let value = decode(&buf)?;
+
+            let index = match parents.last_mut() {
+                Some((parent, remaining)) => {
+                    *remaining -= 1;
+
+                    tree.insert_child_at(*parent, value)
+                }
+                None => {
+                    roots -= 1;
+
+                    let index = tree.allocate_node(Node::new(value));
+                    match last_root {
+                        Some(last) => tree.relate(index, None, Some(last), None),
+                        None => {
+                            tree.first_node = Some(index);
+                            tree.last_node = Some(index);
+                        }
+                    }
+                    last_root = Some(index);
+
+                    index
+                }
+            };
+
+            if children > 0 {
+                parents.push((index, children));
+            }
+        }
+
+        let expected = checksum.crc.finish();
+
+        let mut crc = [0; 4];
+        reader.read_exact(&mut crc)?;
+        if u32::from_le_bytes(crc) != expected {
+            return Err(invalid_data("checksum mismatch"));
+        }
+
+        Ok(tree)
+    }
+
+    fn roots_count(&self) -> usize {
+        let mut count = 0;
+
+        let mut root = self.first_node;
+        while let Some(index) = root {
+            count += 1;
+            root = self.nodes[index].unwrap_ref().next_sibling;
+        }
+
+        count
+    }
+
+    fn children_count(&self, node: &Node<T>) -> usize {
+        let mut count = 0;
+
+        let mut child = node.first_child;
+        while let Some(index) = child {
+            count += 1;
+            child = self.nodes[index].unwrap_ref().next_sibling;
+        }
+
+        count
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn write_varint<W: Write>(writer: &mut W, value: usize) -> io::Result<()> {
+    let mut value = value as u64;
+
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let mut value: u64 = 0;
+    let mut shift = 0;
+
+    loop {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+        let byte = byte[0];
+
+        if shift == 63 && byte > 1 || shift > 63 {
+            return Err(invalid_data("varint overflows 64 bits"));
+        }
+
+        value |= u64::from(byte & 0x7f) << shift;
+
+        if byte & 0x80 == 0 {
+            break;
+        }
+
+        shift += 7;
+    }
+
+    usize::try_from(value).map_err(|_| invalid_data("varint overflows usize"))
+}
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+/// CRC-32 with the IEEE polynomial.
+struct Crc32(u32);
+
+impl Crc32 {
+    fn new() -> Self {
+        Self(!0)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = CRC_TABLE[((self.0 ^ u32::from(*byte)) & 0xff) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+struct ChecksumWriter<'a, W> {
+    inner: &'a mut W,
+    crc: Crc32,
+}
+
+impl<W: Write> Write for ChecksumWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.crc.update(&buf[..written]);
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+struct ChecksumReader<'a, R> {
+    inner: &'a mut R,
+    crc: Crc32,
+}
+
+impl<R: Read> Read for ChecksumReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.crc.update(&buf[..read]);
+
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, ErrorKind};
+
+    use crate::tree::Tree;
+    use pretty_assertions::assert_eq;
+
+    use super::Crc32;
+
+    fn encode(value: &String, buf: &mut Vec<u8>) -> io::Result<()> {
+        buf.extend_from_slice(value.as_bytes());
+
+        Ok(())
+    }
+
+    fn decode(buf: &[u8]) -> io::Result<String> {
+        String::from_utf8(buf.to_vec()).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+    }
+
+    fn encoded(tree: &Tree<String>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        tree.write_to(&mut buf, encode).unwrap();
+
+        buf
+    }
+
+    #[test]
+    fn should_compute_crc32() {
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+
+        assert_eq!(0xcbf4_3926, crc.finish());
+    }
+
+    #[test]
+    fn should_round_trip() {
+        // Allocated in depth-first order, so the decoded tree has the same arena
+        let mut tree = Tree::new();
+        // a -> b -> c
+        //        -> d
+        //   -> e -> f -> g
+        // h
+        // i -> j
+        let a = tree.append_child("a".to_string());
+        let b = tree.append_child_to(&a, "b".to_string()).unwrap();
+        tree.append_child_to(&b, "c".to_string()).unwrap();
+        tree.append_child_to(&b, "d".to_string()).unwrap();
+        let e = tree.append_child_to(&a, "e".to_string()).unwrap();
+        let f = tree.append_child_to(&e, "f".to_string()).unwrap();
+        tree.append_child_to(&f, "g".to_string()).unwrap();
+        let h = tree.insert_sibling_after(&a, "h".to_string()).unwrap();
+        let i = tree.insert_sibling_after(&h, "i".to_string()).unwrap();
+        tree.append_child_to(&i, "j".to_string()).unwrap();
+
+        let buf = encoded(&tree);
+        let decoded = Tree::read_from(&mut buf.as_slice(), decode).unwrap();
+
+        assert_eq!(tree.nodes, decoded.nodes);
+    }
+
+    #[test]
+    fn should_round_trip_empty() {
+        let tree = Tree::new();
+
+        let buf = encoded(&tree);
+        let decoded = Tree::read_from(&mut buf.as_slice(), decode).unwrap();
+
+        assert_eq!(None, decoded.first_node_id());
+    }
+
+    #[test]
+    fn should_skip_removed_nodes() {
+        let mut tree = Tree::new();
+        // a -> b
+        // c -> d
+        let a = tree.append_child("a".to_string());
+        tree.append_child_to(&a, "b".to_string()).unwrap();
+        let c = tree.insert_sibling_after(&a, "c".to_string()).unwrap();
+        tree.append_child_to(&c, "d".to_string()).unwrap();
+
+        tree.remove(a).unwrap();
+
+        let buf = encoded(&tree);
+        let decoded = Tree::read_from(&mut buf.as_slice(), decode).unwrap();
+
+        // The remaining nodes are still allocated in depth-first order
+        tree.compact();
+
+        assert_eq!(tree.nodes, decoded.nodes);
+    }
+
+    #[test]
+    fn should_fail_on_truncated_data() {
+        let mut tree = Tree::new();
+        // a -> b
+        // c
+        let a = tree.append_child("a".to_string());
+        tree.append_child_to(&a, "b".to_string()).unwrap();
+        tree.insert_sibling_after(&a, "c".to_string()).unwrap();
+
+        let buf = encoded(&tree);
+
+        for len in 0..buf.len() {
+            let err = Tree::read_from(&mut &buf[..len], decode).unwrap_err();
+
+            assert_eq!(ErrorKind::UnexpectedEof, err.kind(), "truncated at {}", len);
+        }
+    }
+
+    #[test]
+    fn should_fail_on_corrupted_data() {
+        let mut tree = Tree::new();
+        // a -> b
+        // c
+        let a = tree.append_child("a".to_string());
+        tree.append_child_to(&a, "b".to_string()).unwrap();
+        tree.insert_sibling_after(&a, "c".to_string()).unwrap();
+
+        let mut buf = encoded(&tree);
+        // Change the value of the first node
+        buf[8] = b'z';
+
+        let err = Tree::read_from(&mut buf.as_slice(), decode).unwrap_err();
+
+        assert_eq!(ErrorKind::InvalidData, err.kind());
+        assert_eq!("checksum mismatch", err.to_string());
+    }
+
+    #[test]
+    fn should_check_header() {
+        let mut tree = Tree::new();
+        // a -> b
+        // c
+        let a = tree.append_child("a".to_string());
+        tree.append_child_to(&a, "b".to_string()).unwrap();
+        tree.insert_sibling_after(&a, "c".to_string()).unwrap();
+
+        let mut buf = encoded(&tree);
+        buf[0] = b'X';
+
+        let err = Tree::read_from(&mut buf.as_slice(), decode).unwrap_err();
+        assert_eq!("invalid magic bytes", err.to_string());
+
+        let mut buf = encoded(&tree);
+        buf[4] = 2;
+
+        let err = Tree::read_from(&mut buf.as_slice(), decode).unwrap_err();
+        assert_eq!("unsupported version", err.to_string());
+    }
+}
@@ -14,15 +14,15 @@ pub struct Cursor<'a, T> {
 }
 
 impl<T> Tree<T> {
-    pub fn cursor(&mut self, id: &NodeId) -> Option<Cursor<T>> {
+    pub fn cursor(&mut self, id: &NodeId) -> Option<Cursor<'_, T>> {
         self.index(id).map(|index| Cursor { index, tree: self })
     }
 
-    pub fn cursor_first(&mut self) -> Option<Cursor<T>> {
+    pub fn cursor_first(&mut self) -> Option<Cursor<'_, T>> {
         self.first_node.map(|index| Cursor { index, tree: self })
     }
 
-    pub fn cursor_last(&mut self) -> Option<Cursor<T>> {
+    pub fn cursor_last(&mut self) -> Option<Cursor<'_, T>> {
         self.last_node.map(|index| Cursor { index, tree: self })
     }
 }
@@ -30,7 +30,7 @@ impl<T> Tree<T> {
 impl<T> Cursor<'_, T> {
     #[must_use]
     pub fn id(&self) -> NodeId {
-        NodeId::new(self.index)
+        self.tree.node_id(self.index)
     }
 
     #[must_use]
@@ -149,13 +149,13 @@ impl<T> Cursor<'_, T> {
     pub fn append_child(&mut self, value: T) -> NodeId {
         let index = self.tree.insert_child_at(self.index, value);
 
-        NodeId::new(index)
+        self.tree.node_id(index)
     }
 
     pub fn append_sibling(&mut self, value: T) -> NodeId {
         let index = self.tree.insert_sibling_at(self.index, value);
 
-        NodeId::new(index)
+        self.tree.node_id(index)
     }
 
     #[must_use]
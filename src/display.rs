@@ -0,0 +1,262 @@
+//! Human readable rendering of the tree, like the `tree` command.
+
+use std::fmt::{self, Display};
+
+use crate::tree::Tree;
+
+/// Characters used to draw the branches of the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayStyle {
+    /// Box drawing characters: `├──`, `└──` and `│`.
+    Unicode,
+    /// ASCII characters: `|--`, `` `-- `` and `|`.
+    Ascii,
+    /// Only indentation, with the given number of spaces for each level.
+    Indent(usize),
+}
+
+impl DisplayStyle {
+    /// Returns the prefix for a node, and the one for the descendants of the node.
+    fn branch(&self, has_next: bool) -> (&'static str, &'static str) {
+        match (self, has_next) {
+            (DisplayStyle::Unicode, true) => ("├── ", "│   "),
+            (DisplayStyle::Unicode, false) => ("└── ", "    "),
+            (DisplayStyle::Ascii, true) => ("|-- ", "|   "),
+            (DisplayStyle::Ascii, false) => ("`-- ", "    "),
+            (DisplayStyle::Indent(_), _) => ("", ""),
+        }
+    }
+}
+
+impl Default for DisplayStyle {
+    fn default() -> Self {
+        DisplayStyle::Unicode
+    }
+}
+
+/// Renders the tree with a node for each line.
+///
+/// The top-level nodes are not indented, and the children are drawn under their parent with the
+/// [`DisplayStyle`].
+#[derive(Debug)]
+pub struct DisplayTree<'a, T> {
+    tree: &'a Tree<T>,
+    /// Root of the rendered subtree, or None for all the tree
+    root: Option<usize>,
+    style: DisplayStyle,
+    max_depth: Option<usize>,
+    show_ids: bool,
+}
+
+impl<T> Tree<T> {
+    /// Returns a wrapper to render the tree with [`Display`].
+    #[must_use]
+    pub fn display(&self) -> DisplayTree<'_, T> {
+        DisplayTree::new(self, None)
+    }
+}
+
+impl<'a, T> DisplayTree<'a, T> {
+    pub(crate) fn new(tree: &'a Tree<T>, root: Option<usize>) -> Self {
+        Self {
+            tree,
+            root,
+            style: DisplayStyle::default(),
+            max_depth: None,
+            show_ids: false,
+        }
+    }
+
+    /// Style of the branches.
+    #[must_use]
+    pub fn style(mut self, style: DisplayStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Don't render the nodes deeper than `max_depth`, the top-level nodes have depth 0.
+    #[must_use]
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Render the [`NodeId`] of the nodes after their value.
+    ///
+    /// [`NodeId`]: crate::id::NodeId
+    #[must_use]
+    pub fn show_ids(mut self, show_ids: bool) -> Self {
+        self.show_ids = show_ids;
+        self
+    }
+}
+
+impl<T: Display> Display for DisplayTree<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nodes = &self.tree.nodes;
+
+        // Prefix for the descendants of each node in the current path
+        let mut prefixes: Vec<&str> = Vec::new();
+        let start = self.root.or(self.tree.first_node);
+        let mut current = start;
+
+        while let Some(index) = current {
+            let node = nodes[index].unwrap_ref();
+            let depth = prefixes.len();
+
+            if Some(index) != start {
+                writeln!(f)?;
+            }
+
+            let (branch, prefix) = if depth > 0 {
+                self.style.branch(node.next_sibling.is_some())
+            } else {
+                ("", "")
+            };
+
+            match self.style {
+                DisplayStyle::Indent(width) => write!(f, "{:1$}", "", depth * width)?,
+                DisplayStyle::Unicode | DisplayStyle::Ascii => {
+                    for prefix in prefixes.iter().skip(1) {
+                        f.write_str(prefix)?;
+                    }
+
+                    f.write_str(branch)?;
+                }
+            }
+
+            write!(f, "{}", node.value)?;
+
+            if self.show_ids {
+                write!(f, " #{}", self.tree.node_id(index))?;
+            }
+
+            let below_max = self.max_depth.map_or(true, |max| depth < max);
+
+            if node.first_child.is_some() && below_max {
+                prefixes.push(prefix);
+                current = node.first_child;
+
+                continue;
+            }
+
+            // Go up the tree until a node has a next sibling, without leaving the subtree
+            let mut next_index = index;
+            loop {
+                if self.root == Some(next_index) {
+                    current = None;
+                    break;
+                }
+
+                let next = nodes[next_index].unwrap_ref();
+                if next.next_sibling.is_some() {
+                    current = next.next_sibling;
+                    break;
+                }
+
+                match next.parent {
+                    Some(parent) => {
+                        prefixes.pop();
+                        next_index = parent;
+                    }
+                    None => {
+                        current = None;
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tree::Tree;
+    use pretty_assertions::assert_eq;
+
+    use super::DisplayStyle;
+
+    #[test]
+    fn should_display_unicode() {
+        let mut tree = Tree::new();
+        // root -> first -> child -> grandchild
+        //      -> second -> last
+        // other
+        let root = tree.append_child("root");
+        let first = tree.append_child("first");
+        tree.append_child("child");
+        tree.append_child("grandchild");
+        tree.insert_sibling_after(&first, "second").unwrap();
+        tree.append_child("last");
+        tree.insert_sibling_after(&root, "other").unwrap();
+
+        assert_eq!(
+            "root
+├── first
+│   └── child
+│       └── grandchild
+└── second
+    └── last
+other",
+            tree.display().to_string()
+        );
+    }
+
+    #[test]
+    fn should_display_ascii_with_ids() {
+        let mut tree = Tree::new();
+        // root -> first -> child
+        //      -> second
+        // other -> reused
+        let root = tree.append_child("root");
+        let first = tree.append_child("first");
+        tree.append_child("child");
+        tree.insert_sibling_after(&first, "second").unwrap();
+        tree.insert_sibling_after(&root, "other").unwrap();
+
+        // Reuse the slot of a removed node
+        let removed = tree.append_child("removed");
+        tree.remove(removed).unwrap();
+        tree.append_child("reused");
+
+        assert_eq!(
+            "root #0v0
+|-- first #1v0
+|   `-- child #2v0
+`-- second #3v0
+other #4v0
+`-- reused #5v1",
+            tree.display()
+                .style(DisplayStyle::Ascii)
+                .show_ids(true)
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn should_display_indent_with_max_depth() {
+        let mut tree = Tree::new();
+        // root -> first -> child -> grandchild
+        // other
+        let root = tree.append_child("root");
+        tree.append_child("first");
+        tree.append_child("child");
+        tree.append_child("grandchild");
+        tree.insert_sibling_after(&root, "other").unwrap();
+
+        assert_eq!(
+            "root\n  first\n    child\nother",
+            tree.display()
+                .style(DisplayStyle::Indent(2))
+                .max_depth(Some(2))
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn should_display_empty() {
+        assert_eq!("", Tree::<i32>::new().display().to_string());
+    }
+}
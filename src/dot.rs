@@ -0,0 +1,170 @@
+//! Export of the tree in the Graphviz DOT format.
+
+use std::fmt::{self, Display};
+
+use crate::{id::NodeId, tree::Tree};
+
+/// Formats the tree as a Graphviz DOT graph.
+///
+/// Each node is labeled with the string returned by the label function, and linked to its
+/// children. The siblings can also be linked with dashed edges.
+pub struct Dot<'a, T, F> {
+    tree: &'a Tree<T>,
+    label: F,
+    siblings: bool,
+}
+
+impl<T> Tree<T> {
+    /// Returns a wrapper to format the tree as a Graphviz DOT graph.
+    pub fn dot<F>(&self, label: F) -> Dot<'_, T, F>
+    where
+        F: Fn(NodeId, &T) -> String,
+    {
+        Dot {
+            tree: self,
+            label,
+            siblings: false,
+        }
+    }
+
+    /// Returns the tree as a Graphviz DOT graph, see [`Tree::dot`].
+    pub fn to_dot<F>(&self, label: F) -> String
+    where
+        F: Fn(NodeId, &T) -> String,
+    {
+        self.dot(label).to_string()
+    }
+}
+
+impl<T, F> Dot<'_, T, F> {
+    /// Link the siblings with dashed edges.
+    #[must_use]
+    pub fn with_siblings(mut self, siblings: bool) -> Self {
+        self.siblings = siblings;
+        self
+    }
+}
+
+/// Escapes a string to be used as a quoted DOT identifier.
+fn escape(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+
+    for c in label.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+impl<T, F> Display for Dot<'_, T, F>
+where
+    F: Fn(NodeId, &T) -> String,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "digraph {{")?;
+
+        let mut root = self.tree.first_node;
+        while let Some(root_index) = root {
+            let mut current = Some(root_index);
+
+            while let Some(index) = current {
+                let node = self.tree.nodes[index].unwrap_ref();
+                let label = (self.label)(self.tree.node_id(index), &node.value);
+
+                writeln!(f, "    n{} [label=\"{}\"];", index, escape(&label))?;
+
+                if let Some(parent) = node.parent {
+                    writeln!(f, "    n{} -> n{};", parent, index)?;
+                }
+
+                if let Some(sibling) = node.next_sibling.filter(|_| self.siblings) {
+                    writeln!(
+                        f,
+                        "    n{} -> n{} [style=dashed, constraint=false];",
+                        index, sibling
+                    )?;
+                }
+
+                current = self.tree.next_in_subtree(root_index, index);
+            }
+
+            root = self.tree.nodes[root_index].unwrap_ref().next_sibling;
+        }
+
+        write!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tree::Tree;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_export_dot() {
+        let mut tree = Tree::new();
+        // root -> first
+        //      -> "second"
+        // other
+        let root = tree.append_child("root");
+        let first = tree.append_child("first");
+        tree.insert_sibling_after(&first, "\"second\"").unwrap();
+        tree.insert_sibling_after(&root, "other").unwrap();
+
+        let dot = tree.to_dot(|_, value| value.to_string());
+
+        assert_eq!(
+            r#"digraph {
+    n0 [label="root"];
+    n1 [label="first"];
+    n0 -> n1;
+    n2 [label="\"second\""];
+    n0 -> n2;
+    n3 [label="other"];
+}"#,
+            dot
+        );
+    }
+
+    #[test]
+    fn should_export_dot_with_siblings() {
+        let mut tree = Tree::new();
+        // root -> first
+        //      -> "second"
+        // other
+        let root = tree.append_child("root");
+        let first = tree.append_child("first");
+        tree.insert_sibling_after(&first, "\"second\"").unwrap();
+        tree.insert_sibling_after(&root, "other").unwrap();
+
+        let dot = tree
+            .dot(|id, value| {
+                if id == root {
+                    format!("{}\n(root)", value)
+                } else {
+                    value.to_string()
+                }
+            })
+            .with_siblings(true)
+            .to_string();
+
+        assert_eq!(
+            r#"digraph {
+    n0 [label="root\n(root)"];
+    n0 -> n3 [style=dashed, constraint=false];
+    n1 [label="first"];
+    n0 -> n1;
+    n1 -> n2 [style=dashed, constraint=false];
+    n2 [label="\"second\""];
+    n0 -> n2;
+    n3 [label="other"];
+}"#,
+            dot
+        );
+    }
+}
@@ -1,9 +1,26 @@
-use crate::{node::Node, tree::Tree};
-
+use crate::{
+    id::{IdMap, NodeId},
+    node::Node,
+    tree::Tree,
+};
+
+/// Slot of the tree arena.
+///
+/// The generation is incremented every time the slot is freed, to invalidate the [`NodeId`]s
+/// pointing to the removed node. When the generation can't be incremented anymore the slot is
+/// retired: it stays free, but it's not added to the free list, so the ids are never reused.
+///
+/// [`NodeId`]: crate::id::NodeId
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum Entry<T> {
-    Free { next_free: Option<usize> },
-    Occupied(Node<T>),
+    Free {
+        next_free: Option<usize>,
+        generation: u32,
+    },
+    Occupied {
+        generation: u32,
+        node: Node<T>,
+    },
 }
 
 impl<T> Entry<T> {
@@ -12,34 +29,40 @@ impl<T> Entry<T> {
     }
 
     pub fn is_node(&self) -> bool {
-        matches!(self, Entry::Occupied(..))
+        matches!(self, Entry::Occupied { .. })
+    }
+
+    pub fn generation(&self) -> u32 {
+        match self {
+            Entry::Free { generation, .. } | Entry::Occupied { generation, .. } => *generation,
+        }
     }
 
     pub fn unwrap(self) -> Node<T> {
         match self {
             Entry::Free { .. } => panic!("the entry is free"),
-            Entry::Occupied(node) => node,
+            Entry::Occupied { node, .. } => node,
         }
     }
 
     pub fn unwrap_ref(&self) -> &Node<T> {
         match self {
             Entry::Free { .. } => panic!("the entry is free"),
-            Entry::Occupied(node) => node,
+            Entry::Occupied { node, .. } => node,
         }
     }
 
     pub fn unwrap_mut(&mut self) -> &mut Node<T> {
         match self {
             Entry::Free { .. } => panic!("the entry is free"),
-            Entry::Occupied(node) => node,
+            Entry::Occupied { node, .. } => node,
         }
     }
 
     pub fn unwrap_free(&self) -> Option<usize> {
         match self {
-            Entry::Free { next_free } => *next_free,
-            Entry::Occupied(_) => panic!("the entry is occupied"),
+            Entry::Free { next_free, .. } => *next_free,
+            Entry::Occupied { .. } => panic!("the entry is occupied"),
         }
     }
 
@@ -49,7 +72,7 @@ impl<T> Entry<T> {
     {
         match self {
             Entry::Free { .. } => None,
-            Entry::Occupied(node) => Some(f(node)),
+            Entry::Occupied { node, .. } => Some(f(node)),
         }
     }
 
@@ -59,20 +82,17 @@ impl<T> Entry<T> {
     {
         match self {
             Entry::Free { .. } => None,
-            Entry::Occupied(node) => Some(f(node)),
+            Entry::Occupied { node, .. } => Some(f(node)),
         }
     }
 }
 
 impl<T> Tree<T> {
-    pub(crate) fn get_first_free(&self) -> usize {
-        self.first_free.unwrap_or(self.nodes.len())
-    }
-
     pub(crate) fn allocate_node(&mut self, node: Node<T>) -> usize {
         match self.first_free {
             Some(index) => {
-                let entry = self.nodes[index].replace(Entry::Occupied(node));
+                let generation = self.nodes[index].generation();
+                let entry = self.nodes[index].replace(Entry::Occupied { generation, node });
 
                 self.first_free = entry.unwrap_free();
 
@@ -81,16 +101,145 @@ impl<T> Tree<T> {
             None => {
                 let index = self.nodes.len();
 
-                self.nodes.push(Entry::Occupied(node));
+                self.nodes.push(Entry::Occupied {
+                    generation: 0,
+                    node,
+                });
 
                 index
             }
         }
     }
 
+    /// Frees the slot, bumping its generation.
+    ///
+    /// The slot is retired if the generation would overflow.
     pub(super) fn free_node(&mut self, index: usize) -> Entry<T> {
-        let next_free = self.first_free.replace(index);
+        let generation = self.nodes[index].generation();
+
+        let (generation, next_free) = match generation.checked_add(1) {
+            Some(generation) => (generation, self.first_free.replace(index)),
+            None => (generation, None),
+        };
+
+        self.nodes[index].replace(Entry::Free {
+            next_free,
+            generation,
+        })
+    }
+
+    /// Moves all the nodes at the start of the arena, removing the free and retired entries.
+    ///
+    /// The compacted tree gets a new tag, so all the previous [`NodeId`]s will be rejected. The
+    /// returned [`IdMap`] maps the previous ids to the new ones.
+    pub fn compact(&mut self) -> IdMap {
+        let old_tag = self.tag;
+
+        let mut len = 0;
+        let new_indexes: Vec<Option<usize>> = self
+            .nodes
+            .iter()
+            .map(|entry| {
+                entry.is_node().then(|| {
+                    len += 1;
+
+                    len - 1
+                })
+            })
+            .collect();
+
+        let nodes = std::mem::replace(&mut self.nodes, Vec::with_capacity(len));
+        let mut map = IdMap::default();
+
+        self.tag = Self::next_tag();
+        self.first_free = None;
+        self.first_node = self.first_node.and_then(|index| new_indexes[index]);
+        self.last_node = self.last_node.and_then(|index| new_indexes[index]);
+
+        for (old, entry) in nodes.into_iter().enumerate() {
+            if let Entry::Occupied {
+                generation,
+                mut node,
+            } = entry
+            {
+                node.map_indexes(|index| new_indexes[index].expect("link to an occupied entry"));
+
+                let new = self.nodes.len();
+                self.nodes.push(Entry::Occupied {
+                    generation: 0,
+                    node,
+                });
+
+                map.insert(NodeId::new(old_tag, old, generation), self.node_id(new));
+            }
+        }
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{entry::Entry, error::Error, tree::Tree};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_compact() {
+        let mut tree = Tree::new();
+
+        let first = tree.append_child(1);
+        let second = tree.append_child(2);
+        let third = tree.append_child(3);
+        let fourth = tree.append_sibling(4);
+
+        tree.remove(first).unwrap();
+        tree.remove(third).unwrap();
+
+        assert_eq!(4, tree.nodes.len());
+
+        let map = tree.compact();
+
+        assert_eq!(2, tree.nodes.len());
+        assert!(tree.nodes.iter().all(|entry| entry.is_node()));
+        assert_eq!(None, tree.first_free);
+        assert_eq!(vec![2, 4], tree.iter().copied().collect::<Vec<_>>());
+
+        assert_eq!(2, map.len());
+        assert_eq!(tree.first_node_id(), map.get(&second));
+        assert_eq!(tree.last_node_id(), map.get(&fourth));
+        assert_eq!(None, map.get(&first));
+
+        assert_eq!(None, tree.get(&second));
+        assert_eq!(Err(Error::ForeignTree), tree.append_child_to(&fourth, 5));
+
+        let new = tree.append_child(5);
+        assert_eq!(Some(&5), tree.get(&new));
+        assert_eq!(vec![2, 4, 5], tree.iter().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_retire_slot_on_generation_overflow() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(1);
+        tree.remove(root).unwrap();
+
+        // Reuse the slot with the last generation
+        tree.nodes[0] = Entry::Free {
+            next_free: None,
+            generation: u32::MAX,
+        };
+        let last = tree.append_child(2);
+        assert_eq!(u32::MAX, last.generation);
+
+        tree.remove(last).unwrap();
+
+        assert_eq!(None, tree.first_free);
+        assert_eq!(u32::MAX, tree.nodes[0].generation());
 
-        self.nodes[index].replace(Entry::Free { next_free })
+        let new = tree.append_child(3);
+        assert_eq!(1, new.index);
+        assert_eq!(None, tree.get(&last));
+        assert_eq!(None, tree.get(&root));
     }
 }
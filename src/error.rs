@@ -3,7 +3,25 @@ pub enum Error {
     // Index
     #[error("invalid node id {0}")]
     Invalid(&'static str),
+    #[error("node id belongs to a different tree")]
+    ForeignTree,
     // Relations
     #[error("same node id provided")]
     SameNode,
+    #[error("the relation would create a cycle")]
+    WouldCreateCycle,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseError<E> {
+    #[error("inconsistent indentation at line {line}")]
+    Indentation { line: usize },
+    #[error("{reason} at line {line}")]
+    Syntax { line: usize, reason: &'static str },
+    #[error("invalid value at line {line}")]
+    Value {
+        line: usize,
+        #[source]
+        error: E,
+    },
 }
@@ -0,0 +1,224 @@
+//! Moves the nodes of a tree into another
+
+use crate::{
+    entry::Entry,
+    error::Error,
+    id::{IdMap, NodeId},
+    tree::Tree,
+};
+
+impl<T> Tree<T> {
+    /// Moves all the nodes of the `other` tree in this one.
+    ///
+    /// Returns the indexes of the top-level nodes of `other` in this tree, still related to each
+    /// other as siblings, and the map from the old ids to the new ones.
+    fn absorb(&mut self, other: Tree<T>) -> (Vec<usize>, IdMap) {
+        let Tree {
+            tag,
+            first_node,
+            nodes,
+            ..
+        } = other;
+
+        let mut new_indexes = vec![None; nodes.len()];
+        let mut map = IdMap::default();
+
+        for (old, entry) in nodes.into_iter().enumerate() {
+            if let Entry::Occupied { generation, node } = entry {
+                let new = self.allocate_node(node);
+
+                new_indexes[old] = Some(new);
+                map.insert(NodeId::new(tag, old, generation), self.node_id(new));
+            }
+        }
+
+        for new in new_indexes.iter().flatten() {
+            self.nodes[*new]
+                .unwrap_mut()
+                .map_indexes(|index| new_indexes[index].expect("link to an occupied entry"));
+        }
+
+        let mut roots = Vec::new();
+        let mut root = first_node.and_then(|index| new_indexes[index]);
+        while let Some(index) = root {
+            roots.push(index);
+
+            root = self.nodes[index].unwrap_ref().next_sibling;
+        }
+
+        (roots, map)
+    }
+
+    /// Moves the top-level nodes of `other` as the last children of `parent`.
+    ///
+    /// The nodes without relations in `other`, will be moved without relations in this tree. The
+    /// returned [`IdMap`] maps the ids of `other` to the ones in this tree.
+    ///
+    /// # Errors
+    ///
+    /// - Fails if the `parent` was removed
+    pub fn graft(&mut self, parent: &NodeId, other: Tree<T>) -> Result<IdMap, Error> {
+        let parent_index = self.try_index(parent, "for parent")?;
+
+        let (roots, map) = self.absorb(other);
+
+        for root in roots {
+            self.unlink(root);
+
+            let last_child = self.nodes[parent_index].unwrap_ref().last_child;
+
+            self.relate(root, Some(parent_index), last_child, None);
+        }
+
+        Ok(map)
+    }
+
+    /// Moves the top-level nodes of `other` as the previous siblings of `sibling`.
+    ///
+    /// See [`Tree::graft`] for the other nodes and the returned [`IdMap`].
+    ///
+    /// # Errors
+    ///
+    /// - Fails if the `sibling` was removed
+    pub fn graft_before(&mut self, sibling: &NodeId, other: Tree<T>) -> Result<IdMap, Error> {
+        let sibling_index = self.try_index(sibling, "for sibling")?;
+
+        let (roots, map) = self.absorb(other);
+
+        for root in roots {
+            self.unlink(root);
+
+            let sibling = self.nodes[sibling_index].unwrap_ref();
+            let parent = sibling.parent;
+            let prev_sibling = sibling.prev_sibling;
+
+            self.relate(root, parent, prev_sibling, Some(sibling_index));
+        }
+
+        Ok(map)
+    }
+
+    /// Moves the top-level nodes of `other` as the next siblings of `sibling`.
+    ///
+    /// See [`Tree::graft`] for the other nodes and the returned [`IdMap`].
+    ///
+    /// # Errors
+    ///
+    /// - Fails if the `sibling` was removed
+    pub fn graft_after(&mut self, sibling: &NodeId, other: Tree<T>) -> Result<IdMap, Error> {
+        let mut sibling_index = self.try_index(sibling, "for sibling")?;
+
+        let (roots, map) = self.absorb(other);
+
+        for root in roots {
+            self.unlink(root);
+
+            let sibling = self.nodes[sibling_index].unwrap_ref();
+            let parent = sibling.parent;
+            let next_sibling = sibling.next_sibling;
+
+            self.relate(root, parent, Some(sibling_index), next_sibling);
+
+            sibling_index = root;
+        }
+
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{error::Error, tree::Tree};
+    use pretty_assertions::assert_eq;
+
+    fn other_tree() -> Tree<i32> {
+        let mut other = Tree::new();
+
+        let first = other.append_child(10);
+        other.append_child(11);
+        other.insert_sibling_after(&first, 20).unwrap();
+
+        other
+    }
+
+    #[test]
+    fn should_graft_as_children() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(0);
+        tree.append_child(1);
+        let sibling = tree.insert_sibling_after(&root, 2).unwrap();
+
+        let other = other_tree();
+        let first = other.first_node_id().unwrap();
+        let last = other.last_node_id().unwrap();
+
+        let map = tree.graft(&root, other).unwrap();
+
+        assert_eq!(
+            vec![0, 1, 10, 11, 20, 2],
+            tree.iter().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(3, map.len());
+        assert_eq!(Some(&10), tree.get(&map.get(&first).unwrap()));
+        assert_eq!(Some(&20), tree.get(&map.get(&last).unwrap()));
+        assert_eq!(Some(sibling), tree.last_node_id());
+
+        let map = tree.graft(&sibling, other_tree()).unwrap();
+
+        assert_eq!(
+            vec![0, 1, 10, 11, 20, 2, 10, 11, 20],
+            tree.iter().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            tree.last_node_id(),
+            map.iter()
+                .map(|(_, new)| new)
+                .find(|id| tree.get(id) == Some(&20))
+        );
+    }
+
+    #[test]
+    fn should_graft_before_and_after() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(0);
+        let child = tree.append_child(1);
+
+        tree.graft_before(&child, other_tree()).unwrap();
+
+        assert_eq!(
+            vec![0, 10, 11, 20, 1],
+            tree.iter().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(child), tree.last_node_id());
+
+        tree.graft_after(&child, other_tree()).unwrap();
+
+        assert_eq!(
+            vec![0, 10, 11, 20, 1, 10, 11, 20],
+            tree.iter().copied().collect::<Vec<_>>()
+        );
+
+        tree.graft_before(&root, other_tree()).unwrap();
+
+        assert_eq!(
+            vec![10, 11, 20, 0, 10, 11, 20, 1, 10, 11, 20],
+            tree.iter().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&10), tree.get(&tree.first_node_id().unwrap()));
+    }
+
+    #[test]
+    fn should_not_graft_on_invalid_node() {
+        let mut tree = Tree::new();
+
+        let id = tree.append_child(0);
+        tree.remove(id).unwrap();
+
+        assert_eq!(
+            Err(Error::Invalid("for parent")),
+            tree.graft(&id, other_tree())
+        );
+    }
+}
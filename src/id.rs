@@ -1,37 +1,112 @@
-use crate::tree::Tree;
+use std::{collections::HashMap, fmt};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use crate::{error::Error, tree::Tree};
+
+/// Identifier of a node in a [`Tree`].
+///
+/// The id stores the generation of the slot it was created for, so it can't alias a different
+/// node allocated in the same slot after the original one was removed. It also stores the tag of
+/// the tree that created it, to reject ids used on a different tree.
+///
+/// With the `serde` feature the id can be serialized, including the tag and the generation. It
+/// will stay valid only for a tree serialized with the flat representation of the `serde::flat`
+/// module, since it keeps the tag and the arena of the tree. A tree deserialized from the nested
+/// representation is a new tree, and it will reject the ids of the original one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NodeId {
-    index: usize,
+    pub(crate) tree: u64,
+    pub(crate) index: usize,
+    pub(crate) generation: u32,
 }
 
 impl NodeId {
-    pub(crate) fn new(index: usize) -> Self {
-        Self { index }
+    pub(crate) fn new(tree: u64, index: usize, generation: u32) -> Self {
+        Self {
+            tree,
+            index,
+            generation,
+        }
+    }
+}
+
+/// Formats the index and the generation of the id, like `3v1`. The tag of the tree is omitted.
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}v{}", self.index, self.generation)
+    }
+}
+
+/// Maps the [`NodeId`]s of nodes moved to a different position, to their new [`NodeId`]s.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdMap {
+    ids: HashMap<NodeId, NodeId>,
+}
+
+impl IdMap {
+    pub(crate) fn insert(&mut self, old: NodeId, new: NodeId) {
+        self.ids.insert(old, new);
+    }
+
+    /// Returns the new id of a moved node.
+    #[must_use]
+    pub fn get(&self, id: &NodeId) -> Option<NodeId> {
+        self.ids.get(id).copied()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Iterates over the pairs of old and new ids, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.ids.iter().map(|(old, new)| (*old, *new))
     }
 }
 
 impl<T> Tree<T> {
     #[must_use]
     pub fn get(&self, id: &NodeId) -> Option<&T> {
-        self.nodes.get(id.index)?.map_ref(|node| &node.value)
+        let index = self.index(id)?;
+
+        self.nodes[index].map_ref(|node| &node.value)
     }
 
     #[must_use]
     pub fn get_mut(&mut self, id: &NodeId) -> Option<&mut T> {
-        self.nodes
-            .get_mut(id.index)?
-            .map_mut(|node| &mut node.value)
+        let index = self.index(id)?;
+
+        self.nodes[index].map_mut(|node| &mut node.value)
     }
 
+    /// Returns the index of the node, if the id is still valid.
     #[must_use]
     pub(crate) fn index(&self, id: &NodeId) -> Option<usize> {
-        self.nodes.get(id.index).and_then(|entry| {
-            if entry.is_node() {
-                Some(id.index)
-            } else {
-                None
-            }
-        })
+        self.try_index(id, "passed").ok()
+    }
+
+    /// Returns the index of the node, or an error if the id is invalid for this tree.
+    ///
+    /// The `name` is used in the [`Error::Invalid`] message to tell which id is invalid.
+    pub(crate) fn try_index(&self, id: &NodeId, name: &'static str) -> Result<usize, Error> {
+        if id.tree != self.tag {
+            return Err(Error::ForeignTree);
+        }
+
+        self.nodes
+            .get(id.index)
+            .filter(|entry| entry.is_node() && entry.generation() == id.generation)
+            .map(|_| id.index)
+            .ok_or(Error::Invalid(name))
+    }
+
+    /// Returns the [`NodeId`] of the node stored at the index.
+    pub(crate) fn node_id(&self, index: usize) -> NodeId {
+        NodeId::new(self.tag, index, self.nodes[index].generation())
     }
 }
@@ -1,9 +1,13 @@
 /// Implements iteration over a tree
 use crate::{entry::Entry, id::NodeId, node::Node, tree::Tree};
 
+pub mod bfs;
+pub mod post_order;
+pub mod traverse;
+
 impl<T> Tree<T> {
     #[must_use]
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             current: self.first_node,
             nodes: &self.nodes,
@@ -11,7 +15,7 @@ impl<T> Tree<T> {
     }
 
     #[must_use]
-    pub fn iter_from(&self, id: &NodeId) -> Iter<T> {
+    pub fn iter_from(&self, id: &NodeId) -> Iter<'_, T> {
         Iter {
             current: self.index(id),
             nodes: &self.nodes,
@@ -19,24 +23,10 @@ impl<T> Tree<T> {
     }
 
     #[must_use]
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             current: self.first_node,
-            nodes: self
-                .nodes
-                .iter_mut()
-                .filter_map(|entry| match entry {
-                    Entry::Free { .. } => None,
-                    Entry::Occupied(node) => Some(Node {
-                        value: Some(&mut node.value),
-                        parent: node.parent,
-                        first_child: node.first_child,
-                        last_child: node.last_child,
-                        next_sibling: node.next_sibling,
-                        prev_sibling: node.prev_sibling,
-                    }),
-                })
-                .collect(),
+            nodes: self.mut_nodes(),
         }
     }
 
@@ -44,23 +34,70 @@ impl<T> Tree<T> {
     pub fn into_iterator(self) -> IntoIter<T> {
         IntoIter {
             current: self.first_node,
-            nodes: self
-                .nodes
-                .into_iter()
-                .filter_map(|entry| match entry {
-                    Entry::Free { .. } => None,
-                    Entry::Occupied(node) => Some(Node {
-                        value: Some(node.value),
-                        parent: node.parent,
-                        first_child: node.first_child,
-                        last_child: node.last_child,
-                        next_sibling: node.next_sibling,
-                        prev_sibling: node.prev_sibling,
-                    }),
-                })
-                .collect(),
+            nodes: self.into_nodes(),
         }
     }
+
+    /// Returns the nodes with mutable references to the values, that can be taken while iterating.
+    pub(crate) fn mut_nodes(&mut self) -> Vec<Node<Option<&mut T>>> {
+        self.nodes
+            .iter_mut()
+            // Keep the free entries, so the indexes of the nodes don't change
+            .map(|entry| match entry {
+                Entry::Free { .. } => Node::new(None),
+                Entry::Occupied { node, .. } => Node {
+                    value: Some(&mut node.value),
+                    parent: node.parent,
+                    first_child: node.first_child,
+                    last_child: node.last_child,
+                    next_sibling: node.next_sibling,
+                    prev_sibling: node.prev_sibling,
+                },
+            })
+            .collect()
+    }
+
+    /// Returns the nodes with the values, that can be taken while iterating.
+    pub(crate) fn into_nodes(self) -> Vec<Node<Option<T>>> {
+        self.nodes
+            .into_iter()
+            // Keep the free entries, so the indexes of the nodes don't change
+            .map(|entry| match entry {
+                Entry::Free { .. } => Node::new(None),
+                Entry::Occupied { node, .. } => Node {
+                    value: Some(node.value),
+                    parent: node.parent,
+                    first_child: node.first_child,
+                    last_child: node.last_child,
+                    next_sibling: node.next_sibling,
+                    prev_sibling: node.prev_sibling,
+                },
+            })
+            .collect()
+    }
+}
+
+/// Access to the relations of the nodes, stored in the tree or copied by an iterator.
+pub(crate) trait AsNode {
+    type Value;
+
+    fn as_node(&self) -> &Node<Self::Value>;
+}
+
+impl<T> AsNode for Entry<T> {
+    type Value = T;
+
+    fn as_node(&self) -> &Node<T> {
+        self.unwrap_ref()
+    }
+}
+
+impl<T> AsNode for Node<T> {
+    type Value = T;
+
+    fn as_node(&self) -> &Node<T> {
+        self
+    }
 }
 
 impl<'a, T> IntoIterator for &'a Tree<T> {
@@ -227,8 +264,6 @@ mod test {
         tree.append_sibling(1);
         tree.append_sibling(2);
 
-        dbg!(&tree);
-
         let mut iter = tree.iter();
 
         assert_eq!(1, *iter.next().unwrap());
@@ -262,8 +297,6 @@ mod test {
         tree.append_sibling(1);
         tree.append_sibling(2);
 
-        dbg!(&tree);
-
         let mut iter = tree.iter_mut();
 
         assert_eq!(1, *iter.next().unwrap());
@@ -297,11 +330,37 @@ mod test {
         tree.append_sibling(1);
         tree.append_sibling(2);
 
-        dbg!(&tree);
-
         let mut iter = tree.into_iterator();
 
         assert_eq!(1, iter.next().unwrap());
         assert_eq!(2, iter.next().unwrap());
     }
+
+    #[test]
+    fn mut_should_iter_with_removed_nodes() {
+        let mut tree = Tree::<i32>::new();
+
+        let removed = tree.append_child(1);
+        tree.append_child(2);
+        tree.append_sibling(3);
+
+        tree.remove(removed).unwrap();
+
+        let values: Vec<i32> = tree.iter_mut().map(|value| *value).collect();
+
+        assert_eq!(vec![2, 3], values);
+    }
+
+    #[test]
+    fn into_should_iter_with_removed_nodes() {
+        let mut tree = Tree::<i32>::new();
+
+        let removed = tree.append_child(1);
+        tree.append_child(2);
+        tree.append_sibling(3);
+
+        tree.remove(removed).unwrap();
+
+        assert_eq!(vec![2, 3], tree.into_iterator().collect::<Vec<_>>());
+    }
 }
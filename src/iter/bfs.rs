@@ -0,0 +1,260 @@
+//! Breadth-first iteration, visiting the nodes level by level.
+
+use std::collections::VecDeque;
+
+use crate::{id::NodeId, tree::Tree};
+
+impl<T> Tree<T> {
+    /// Returns an iterator visiting the nodes level by level, starting from the roots.
+    #[must_use]
+    pub fn iter_bfs(&self) -> Bfs<'_, T> {
+        let mut queue = VecDeque::new();
+
+        let mut root = self.first_node;
+        while let Some(index) = root {
+            queue.push_back((0, index));
+            root = self.nodes[index].unwrap_ref().next_sibling;
+        }
+
+        Bfs { tree: self, queue }
+    }
+
+    /// Returns an iterator visiting the subtree of the node level by level.
+    ///
+    /// The iterator is empty if the id is invalid.
+    #[must_use]
+    pub fn iter_bfs_from(&self, id: &NodeId) -> Bfs<'_, T> {
+        Bfs {
+            tree: self,
+            queue: self.index(id).map(|index| (0, index)).into_iter().collect(),
+        }
+    }
+
+    /// Returns an iterator over the values of each level, starting from the roots.
+    #[must_use]
+    pub fn levels(&self) -> Levels<'_, T> {
+        let mut level = Vec::new();
+
+        let mut root = self.first_node;
+        while let Some(index) = root {
+            level.push(index);
+            root = self.nodes[index].unwrap_ref().next_sibling;
+        }
+
+        Levels { tree: self, level }
+    }
+}
+
+#[derive(Debug)]
+pub struct Bfs<'a, T> {
+    tree: &'a Tree<T>,
+    /// Depth and index of the nodes to visit
+    queue: VecDeque<(usize, usize)>,
+}
+
+impl<'a, T> Bfs<'a, T> {
+    /// Yields the depth and the id of the nodes with their values.
+    ///
+    /// The depth is relative to the node the iteration started from.
+    #[must_use]
+    pub fn with_depth(self) -> BfsWithDepth<'a, T> {
+        BfsWithDepth { inner: self }
+    }
+
+    fn next_node(&mut self) -> Option<(usize, usize)> {
+        let (depth, index) = self.queue.pop_front()?;
+
+        let mut child = self.tree.nodes[index].unwrap_ref().first_child;
+        while let Some(child_index) = child {
+            self.queue.push_back((depth + 1, child_index));
+            child = self.tree.nodes[child_index].unwrap_ref().next_sibling;
+        }
+
+        Some((depth, index))
+    }
+}
+
+impl<'a, T> Iterator for Bfs<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree = self.tree;
+
+        self.next_node()
+            .map(|(_, index)| &tree.nodes[index].unwrap_ref().value)
+    }
+}
+
+#[derive(Debug)]
+pub struct BfsWithDepth<'a, T> {
+    inner: Bfs<'a, T>,
+}
+
+impl<'a, T> Iterator for BfsWithDepth<'a, T> {
+    type Item = (usize, NodeId, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree = self.inner.tree;
+
+        self.inner.next_node().map(|(depth, index)| {
+            (
+                depth,
+                tree.node_id(index),
+                &tree.nodes[index].unwrap_ref().value,
+            )
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Levels<'a, T> {
+    tree: &'a Tree<T>,
+    /// Indexes of the nodes in the next level
+    level: Vec<usize>,
+}
+
+impl<'a, T> Iterator for Levels<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.level.is_empty() {
+            return None;
+        }
+
+        let tree = self.tree;
+        let mut next_level = Vec::new();
+
+        let values = self
+            .level
+            .iter()
+            .map(|index| {
+                let node = tree.nodes[*index].unwrap_ref();
+
+                let mut child = node.first_child;
+                while let Some(child_index) = child {
+                    next_level.push(child_index);
+                    child = tree.nodes[child_index].unwrap_ref().next_sibling;
+                }
+
+                &node.value
+            })
+            .collect();
+
+        self.level = next_level;
+
+        Some(values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tree::Tree;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_return_none_on_empty() {
+        let tree = Tree::<i32>::new();
+
+        assert_eq!(None, tree.iter_bfs().next());
+        assert_eq!(None, tree.levels().next());
+    }
+
+    #[test]
+    fn should_iter_level_by_level() {
+        let mut tree = Tree::new();
+        // 1 -> 2 -> 4
+        //        -> 5
+        //   -> 3 -> 6
+        // 7 -> 8
+        let root = tree.append_child(1);
+        let two = tree.append_child_to(&root, 2).unwrap();
+        tree.append_child_to(&two, 4).unwrap();
+        tree.append_child_to(&two, 5).unwrap();
+        let three = tree.append_child_to(&root, 3).unwrap();
+        tree.append_child_to(&three, 6).unwrap();
+        let seven = tree.insert_sibling_after(&root, 7).unwrap();
+        tree.append_child_to(&seven, 8).unwrap();
+
+        assert_eq!(
+            vec![1, 7, 2, 3, 8, 4, 5, 6],
+            tree.iter_bfs().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_iter_from_node() {
+        let mut tree = Tree::new();
+        // 1 -> 2 -> 4
+        //        -> 5
+        //   -> 3
+        let root = tree.append_child(1);
+        let two = tree.append_child(2);
+        tree.append_child(4);
+        tree.append_child_to(&two, 5).unwrap();
+        tree.insert_sibling_after(&two, 3).unwrap();
+
+        assert_eq!(
+            vec![2, 4, 5],
+            tree.iter_bfs_from(&two).copied().collect::<Vec<_>>()
+        );
+
+        tree.remove(two).unwrap();
+
+        assert_eq!(None, tree.iter_bfs_from(&two).next());
+        assert_eq!(
+            vec![1, 4, 5, 3],
+            tree.iter_bfs_from(&root).copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_report_depth() {
+        let mut tree = Tree::new();
+        // 1 -> 2 -> 3
+        // 4
+        let root = tree.append_child(1);
+        tree.append_child(2);
+        tree.append_child(3);
+        tree.insert_sibling_after(&root, 4).unwrap();
+
+        let depths: Vec<(usize, i32)> = tree
+            .iter_bfs()
+            .with_depth()
+            .map(|(depth, id, value)| {
+                assert_eq!(Some(value), tree.get(&id));
+
+                (depth, *value)
+            })
+            .collect();
+
+        assert_eq!(vec![(0, 1), (0, 4), (1, 2), (2, 3)], depths);
+
+        let depths: Vec<usize> = tree
+            .iter_bfs_from(&root)
+            .with_depth()
+            .map(|(depth, _, _)| depth)
+            .collect();
+
+        assert_eq!(vec![0, 1, 2], depths);
+    }
+
+    #[test]
+    fn should_iter_levels() {
+        let mut tree = Tree::new();
+        // 1 -> 2 -> 4
+        //   -> 3
+        // 5
+        let root = tree.append_child(1);
+        let two = tree.append_child(2);
+        tree.append_child(4);
+        tree.insert_sibling_after(&two, 3).unwrap();
+        tree.insert_sibling_after(&root, 5).unwrap();
+
+        let levels: Vec<Vec<i32>> = tree
+            .levels()
+            .map(|level| level.into_iter().copied().collect())
+            .collect();
+
+        assert_eq!(vec![vec![1, 5], vec![2, 3], vec![4]], levels);
+    }
+}
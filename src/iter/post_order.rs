@@ -0,0 +1,210 @@
+//! Post-order iteration, visiting the children before their parent.
+
+use crate::{entry::Entry, node::Node, tree::Tree};
+
+use super::AsNode;
+
+impl<T> Tree<T> {
+    /// Returns an iterator visiting the children before their parent.
+    #[must_use]
+    pub fn iter_post_order(&self) -> PostOrderIter<'_, T> {
+        PostOrderIter {
+            current: self.first_node.map(|root| first_leaf(&self.nodes, root)),
+            nodes: &self.nodes,
+        }
+    }
+
+    /// Returns an iterator visiting the children before their parent, with mutable values.
+    #[must_use]
+    pub fn iter_post_order_mut(&mut self) -> PostOrderIterMut<'_, T> {
+        let first_node = self.first_node;
+        let nodes = self.mut_nodes();
+
+        PostOrderIterMut {
+            current: first_node.map(|root| first_leaf(&nodes, root)),
+            nodes,
+        }
+    }
+
+    /// Returns an iterator consuming the tree, and visiting the children before their parent.
+    #[must_use]
+    pub fn into_iter_post_order(self) -> PostOrderIntoIter<T> {
+        let first_node = self.first_node;
+        let nodes = self.into_nodes();
+
+        PostOrderIntoIter {
+            current: first_node.map(|root| first_leaf(&nodes, root)),
+            nodes,
+        }
+    }
+}
+
+/// Follows the first children from the node down to a leaf.
+fn first_leaf<N: AsNode>(nodes: &[N], mut index: usize) -> usize {
+    while let Some(child) = nodes[index].as_node().first_child {
+        index = child;
+    }
+
+    index
+}
+
+/// Returns the node visited after the current one.
+fn next_post_order<N: AsNode>(nodes: &[N], current: usize) -> Option<usize> {
+    let node = nodes[current].as_node();
+
+    // The subtree of the next sibling comes before the parent
+    match node.next_sibling {
+        Some(sibling) => Some(first_leaf(nodes, sibling)),
+        None => node.parent,
+    }
+}
+
+#[derive(Debug)]
+pub struct PostOrderIter<'a, T> {
+    current: Option<usize>,
+    nodes: &'a [Entry<T>],
+}
+
+impl<'a, T> Iterator for PostOrderIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.current.take().map(|current| {
+            self.current = next_post_order(self.nodes, current);
+
+            &self.nodes[current].unwrap_ref().value
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PostOrderIterMut<'a, T> {
+    current: Option<usize>,
+    nodes: Vec<Node<Option<&'a mut T>>>,
+}
+
+impl<'a, T> Iterator for PostOrderIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.current.take().and_then(|current| {
+            self.current = next_post_order(&self.nodes, current);
+
+            self.nodes[current].value.take()
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PostOrderIntoIter<T> {
+    current: Option<usize>,
+    nodes: Vec<Node<Option<T>>>,
+}
+
+impl<T> Iterator for PostOrderIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.current.take().and_then(|current| {
+            self.current = next_post_order(&self.nodes, current);
+
+            self.nodes[current].value.take()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tree::Tree;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_return_none_on_empty() {
+        let mut tree = Tree::<i32>::new();
+
+        assert_eq!(None, tree.iter_post_order().next());
+        assert_eq!(None, tree.iter_post_order_mut().next());
+        assert_eq!(None, tree.into_iter_post_order().next());
+    }
+
+    #[test]
+    fn should_visit_children_before_parents() {
+        let mut tree = Tree::new();
+        // 1 -> 2 -> 3
+        //        -> 4
+        //   -> 5 -> 6
+        // 7
+        // 8 -> 9
+        let root = tree.append_child(1);
+        let two = tree.append_child_to(&root, 2).unwrap();
+        tree.append_child_to(&two, 3).unwrap();
+        tree.append_child_to(&two, 4).unwrap();
+        let five = tree.append_child_to(&root, 5).unwrap();
+        tree.append_child_to(&five, 6).unwrap();
+        let seven = tree.insert_sibling_after(&root, 7).unwrap();
+        let eight = tree.insert_sibling_after(&seven, 8).unwrap();
+        tree.append_child_to(&eight, 9).unwrap();
+
+        let expected = vec![3, 4, 2, 6, 5, 1, 7, 9, 8];
+
+        assert_eq!(
+            expected,
+            tree.iter_post_order().copied().collect::<Vec<_>>()
+        );
+
+        let values: Vec<i32> = tree.iter_post_order_mut().map(|value| *value).collect();
+        assert_eq!(expected, values);
+
+        assert_eq!(expected, tree.into_iter_post_order().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_mutate_values() {
+        let mut tree = Tree::new();
+        // 1 -> 2
+        // 3
+        let root = tree.append_child(1);
+        tree.append_child(2);
+        tree.insert_sibling_after(&root, 3).unwrap();
+
+        tree.iter_post_order_mut().for_each(|value| *value *= 10);
+
+        assert_eq!(vec![10, 20, 30], tree.iter().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_iter_with_removed_nodes() {
+        let mut tree = Tree::new();
+        // 1 -> 2 -> 3
+        //   -> 4
+        // 5
+        let root = tree.append_child(1);
+        let two = tree.append_child(2);
+        tree.append_child(3);
+        tree.insert_sibling_after(&two, 4).unwrap();
+        tree.insert_sibling_after(&root, 5).unwrap();
+
+        tree.remove(root).unwrap();
+
+        assert_eq!(
+            vec![3, 2, 4, 5],
+            tree.iter_post_order().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![3, 2, 4, 5],
+            tree.into_iter_post_order().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_iter_deep_tree() {
+        let mut tree = Tree::new();
+
+        for value in 0..100_000 {
+            tree.append_child(value);
+        }
+
+        assert_eq!(Some(&99_999), tree.iter_post_order().next());
+        assert_eq!(Some(0), tree.into_iter_post_order().last());
+    }
+}
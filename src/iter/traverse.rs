@@ -0,0 +1,270 @@
+//! Traversal of the tree as the opening and closing edges of the nodes.
+
+use crate::{id::NodeId, tree::Tree};
+
+/// Event of a traversal, entering or leaving a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edge {
+    /// Entering the node, before its descendants.
+    Open(NodeId),
+    /// Leaving the node, after its descendants.
+    Close(NodeId),
+}
+
+/// Edge with the index of the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IndexEdge {
+    Open(usize),
+    Close(usize),
+}
+
+impl<T> Tree<T> {
+    /// Returns an iterator over the edges of all the nodes, in document order.
+    ///
+    /// Each node is opened, then all its descendants are traversed and then it's closed.
+    #[must_use]
+    pub fn traverse(&self) -> Traverse<'_, T> {
+        Traverse {
+            tree: self,
+            root: None,
+            front: self.first_node.map(IndexEdge::Open),
+            back: self.last_root().map(IndexEdge::Close),
+        }
+    }
+
+    /// Returns an iterator over the edges of the subtree of the node, in document order.
+    ///
+    /// The iterator is empty if the id is invalid.
+    #[must_use]
+    pub fn traverse_from(&self, id: &NodeId) -> Traverse<'_, T> {
+        let index = self.index(id);
+
+        Traverse {
+            tree: self,
+            root: index,
+            front: index.map(IndexEdge::Open),
+            back: index.map(IndexEdge::Close),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Traverse<'a, T> {
+    tree: &'a Tree<T>,
+    /// Root of the traversed subtree, or None for all the tree
+    root: Option<usize>,
+    front: Option<IndexEdge>,
+    back: Option<IndexEdge>,
+}
+
+impl<T> Traverse<'_, T> {
+    fn edge(&self, edge: IndexEdge) -> Edge {
+        match edge {
+            IndexEdge::Open(index) => Edge::Open(self.tree.node_id(index)),
+            IndexEdge::Close(index) => Edge::Close(self.tree.node_id(index)),
+        }
+    }
+
+    fn next_edge(&self, edge: IndexEdge) -> Option<IndexEdge> {
+        match edge {
+            IndexEdge::Open(index) => {
+                let node = self.tree.nodes[index].unwrap_ref();
+
+                Some(match node.first_child {
+                    Some(child) => IndexEdge::Open(child),
+                    None => IndexEdge::Close(index),
+                })
+            }
+            IndexEdge::Close(index) => {
+                if self.root == Some(index) {
+                    return None;
+                }
+
+                let node = self.tree.nodes[index].unwrap_ref();
+
+                match (node.next_sibling, node.parent) {
+                    (Some(sibling), _) => Some(IndexEdge::Open(sibling)),
+                    (None, Some(parent)) => Some(IndexEdge::Close(parent)),
+                    (None, None) => None,
+                }
+            }
+        }
+    }
+
+    fn prev_edge(&self, edge: IndexEdge) -> Option<IndexEdge> {
+        match edge {
+            IndexEdge::Close(index) => {
+                let node = self.tree.nodes[index].unwrap_ref();
+
+                Some(match node.last_child {
+                    Some(child) => IndexEdge::Close(child),
+                    None => IndexEdge::Open(index),
+                })
+            }
+            IndexEdge::Open(index) => {
+                if self.root == Some(index) {
+                    return None;
+                }
+
+                let node = self.tree.nodes[index].unwrap_ref();
+
+                match (node.prev_sibling, node.parent) {
+                    (Some(sibling), _) => Some(IndexEdge::Close(sibling)),
+                    (None, Some(parent)) => Some(IndexEdge::Open(parent)),
+                    (None, None) => None,
+                }
+            }
+        }
+    }
+}
+
+impl<T> Iterator for Traverse<'_, T> {
+    type Item = Edge;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let edge = self.front?;
+
+        // Stop when the two ends meet
+        if self.back == Some(edge) {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.front = self.next_edge(edge);
+        }
+
+        Some(self.edge(edge))
+    }
+}
+
+impl<T> DoubleEndedIterator for Traverse<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let edge = self.back?;
+
+        // Stop when the two ends meet
+        if self.front == Some(edge) {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.back = self.prev_edge(edge);
+        }
+
+        Some(self.edge(edge))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tree::Tree;
+    use pretty_assertions::assert_eq;
+
+    use super::Edge;
+
+    /// Writes the edges as nested tags.
+    fn tags<I>(tree: &Tree<String>, edges: I) -> String
+    where
+        I: Iterator<Item = Edge>,
+    {
+        edges
+            .map(|edge| match edge {
+                Edge::Open(id) => format!("<{}>", tree.get(&id).unwrap()),
+                Edge::Close(id) => format!("</{}>", tree.get(&id).unwrap()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_return_none_on_empty() {
+        let tree = Tree::<i32>::new();
+
+        assert_eq!(None, tree.traverse().next());
+        assert_eq!(None, tree.traverse().next_back());
+    }
+
+    #[test]
+    fn should_traverse_in_document_order() {
+        let mut tree = Tree::new();
+        // a -> b -> c
+        //        -> d
+        //   -> e
+        // f -> g
+        let a = tree.append_child("a".to_string());
+        let b = tree.append_child_to(&a, "b".to_string()).unwrap();
+        tree.append_child_to(&b, "c".to_string()).unwrap();
+        tree.append_child_to(&b, "d".to_string()).unwrap();
+        tree.append_child_to(&a, "e".to_string()).unwrap();
+        let f = tree.insert_sibling_after(&a, "f".to_string()).unwrap();
+        tree.append_child_to(&f, "g".to_string()).unwrap();
+
+        assert_eq!(
+            "<a><b><c></c><d></d></b><e></e></a><f><g></g></f>",
+            tags(&tree, tree.traverse())
+        );
+    }
+
+    #[test]
+    fn should_traverse_backwards() {
+        let mut tree = Tree::new();
+        // a -> b -> c
+        //   -> d
+        // e
+        let a = tree.append_child("a".to_string());
+        let b = tree.append_child("b".to_string());
+        tree.append_child("c".to_string());
+        tree.insert_sibling_after(&b, "d".to_string()).unwrap();
+        tree.insert_sibling_after(&a, "e".to_string()).unwrap();
+
+        let mut forward: Vec<Edge> = tree.traverse().collect();
+        forward.reverse();
+
+        assert_eq!(forward, tree.traverse().rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_meet_in_the_middle() {
+        let mut tree = Tree::new();
+        // a -> b -> c
+        //   -> d
+        // e
+        let a = tree.append_child("a".to_string());
+        let b = tree.append_child("b".to_string());
+        tree.append_child("c".to_string());
+        tree.insert_sibling_after(&b, "d".to_string()).unwrap();
+        tree.insert_sibling_after(&a, "e".to_string()).unwrap();
+
+        let all: Vec<Edge> = tree.traverse().collect();
+
+        for split in 0..=all.len() {
+            let mut traverse = tree.traverse();
+
+            let mut front: Vec<Edge> = traverse.by_ref().take(split).collect();
+            let back: Vec<Edge> = traverse.rev().collect();
+
+            front.extend(back.into_iter().rev());
+
+            assert_eq!(all, front);
+        }
+    }
+
+    #[test]
+    fn should_traverse_from_node() {
+        let mut tree = Tree::new();
+        // a -> b -> c
+        //        -> d
+        //   -> e
+        let a = tree.append_child("a".to_string());
+        let b = tree.append_child_to(&a, "b".to_string()).unwrap();
+        let c = tree.append_child_to(&b, "c".to_string()).unwrap();
+        tree.append_child_to(&b, "d".to_string()).unwrap();
+        tree.append_child_to(&a, "e".to_string()).unwrap();
+
+        assert_eq!("<b><c></c><d></d></b>", tags(&tree, tree.traverse_from(&b)));
+        assert_eq!(
+            "</b></d><d></c><c><b>",
+            tags(&tree, tree.traverse_from(&b).rev())
+        );
+
+        // Doesn't continue to the siblings
+        assert_eq!("<c></c>", tags(&tree, tree.traverse_from(&c)));
+        assert_eq!("</c><c>", tags(&tree, tree.traverse_from(&c).rev()));
+    }
+}
@@ -1,10 +1,19 @@
+pub mod codec;
 pub mod cursor;
+pub mod display;
+pub mod dot;
 pub mod entry;
 pub mod error;
+pub mod graft;
 pub mod id;
 pub mod iter;
 pub mod node;
+pub mod outline;
+#[cfg(feature = "rayon")]
+pub mod rayon;
 pub mod relate;
 #[cfg(feature = "serde")]
 pub mod serde;
+pub mod sexpr;
+pub mod subtree;
 pub mod tree;
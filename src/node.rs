@@ -20,6 +20,18 @@ impl<T> Node<T> {
             last_child: None,
         }
     }
+
+    /// Changes the indexes of the related nodes.
+    pub(crate) fn map_indexes<F>(&mut self, f: F)
+    where
+        F: Fn(usize) -> usize,
+    {
+        self.parent = self.parent.map(&f);
+        self.prev_sibling = self.prev_sibling.map(&f);
+        self.next_sibling = self.next_sibling.map(&f);
+        self.first_child = self.first_child.map(&f);
+        self.last_child = self.last_child.map(&f);
+    }
 }
 
 impl<T: Default> Default for Node<T> {
@@ -0,0 +1,206 @@
+//! Conversion between trees and indented text outlines.
+//!
+//! Each line of the outline is a node, and the children are indented under their parent:
+//!
+//! ```text
+//! root
+//!     child
+//!         grandchild
+//!     child
+//! other root
+//! ```
+
+use crate::{error::ParseError, id::NodeId, tree::Tree};
+
+impl<T> Tree<T> {
+    /// Parses a tree from an indented outline.
+    ///
+    /// The children must be indented more than their parent, and the siblings with the same
+    /// whitespace. Empty lines are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the line number if the indentation is inconsistent, or a value can't be
+    /// parsed.
+    pub fn from_indented<F, E>(outline: &str, mut parse: F) -> Result<Self, ParseError<E>>
+    where
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        let mut tree = Tree::new();
+        // Indentation and id of the last node for each level
+        let mut levels: Vec<(&str, NodeId)> = Vec::new();
+
+        for (line_index, line) in outline.lines().enumerate() {
+            let line_number = line_index + 1;
+
+            let content = line.trim_start();
+            if content.is_empty() {
+                continue;
+            }
+
+            let indent = &line[..line.len() - content.len()];
+            let content = content.trim_end();
+
+            let value = parse(content).map_err(|error| ParseError::Value {
+                line: line_number,
+                error,
+            })?;
+
+            let indentation = || ParseError::Indentation { line: line_number };
+
+            let id = match levels.last() {
+                None => {
+                    if !indent.is_empty() {
+                        return Err(indentation());
+                    }
+
+                    tree.append_child(value)
+                }
+                Some((last_indent, last)) if indent.len() > last_indent.len() => {
+                    if !indent.starts_with(last_indent) {
+                        return Err(indentation());
+                    }
+
+                    let last = *last;
+                    tree.append_child_to(&last, value)
+                        .expect("the node is in the tree")
+                }
+                Some(_) => {
+                    while levels
+                        .last()
+                        .map_or(false, |(last_indent, _)| last_indent.len() > indent.len())
+                    {
+                        levels.pop();
+                    }
+
+                    let (last_indent, last) = levels.pop().ok_or_else(indentation)?;
+
+                    if last_indent != indent {
+                        return Err(indentation());
+                    }
+
+                    tree.insert_sibling_after(&last, value)
+                        .expect("the node is in the tree")
+                }
+            };
+
+            levels.push((indent, id));
+        }
+
+        Ok(tree)
+    }
+
+    /// Writes the tree as an indented outline, that can be parsed with [`Tree::from_indented`].
+    ///
+    /// Each level is indented with `indent`, and the values must be formatted on a single line.
+    pub fn to_indented<F>(&self, indent: &str, mut format: F) -> String
+    where
+        F: FnMut(&T) -> String,
+    {
+        let mut outline = String::new();
+
+        let mut root = self.first_node;
+        while let Some(root_index) = root {
+            let mut current = Some(root_index);
+            let mut depth = 0;
+
+            while let Some(index) = current {
+                let node = self.nodes[index].unwrap_ref();
+
+                outline.push_str(&indent.repeat(depth));
+                outline.push_str(&format(&node.value));
+                outline.push('\n');
+
+                current = self.next_in_subtree(root_index, index);
+
+                // Find the depth of the next node from its relation with the current one
+                if let Some(next) = current {
+                    let next_parent = self.nodes[next].unwrap_ref().parent;
+
+                    if next_parent == Some(index) {
+                        depth += 1;
+                    } else {
+                        let mut ancestor = node.parent;
+                        while ancestor != next_parent {
+                            depth -= 1;
+                            ancestor =
+                                ancestor.and_then(|index| self.nodes[index].unwrap_ref().parent);
+                        }
+                    }
+                }
+            }
+
+            root = self.nodes[root_index].unwrap_ref().next_sibling;
+        }
+
+        outline
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::ParseIntError;
+
+    use crate::{error::ParseError, tree::Tree};
+    use pretty_assertions::assert_eq;
+
+    fn parse(value: &str) -> Result<i32, ParseIntError> {
+        value.parse()
+    }
+
+    #[test]
+    fn should_parse_outline() {
+        let outline = "1
+    2
+        3
+
+    4
+\t
+5
+";
+
+        let tree = Tree::from_indented(outline, parse).unwrap();
+
+        assert_eq!("1\n├── 2\n│   └── 3\n└── 4\n5", tree.display().to_string());
+    }
+
+    #[test]
+    fn should_round_trip_outline() {
+        let outline = "1\n  2\n    3\n      4\n  5\n6\n  7\n";
+
+        let tree = Tree::from_indented(outline, parse).unwrap();
+
+        assert_eq!(outline, tree.to_indented("  ", ToString::to_string));
+    }
+
+    #[test]
+    fn should_reject_inconsistent_indentation() {
+        let first_indented = "  1\n2";
+        assert_eq!(
+            Err(ParseError::Indentation { line: 1 }),
+            Tree::from_indented(first_indented, parse).map(|_| ())
+        );
+
+        let dedent = "1\n    2\n        3\n  4";
+        assert_eq!(
+            Err(ParseError::Indentation { line: 4 }),
+            Tree::from_indented(dedent, parse).map(|_| ())
+        );
+
+        let mixed = "1\n\t2\n    3";
+        assert_eq!(
+            Err(ParseError::Indentation { line: 3 }),
+            Tree::from_indented(mixed, parse).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn should_report_invalid_value() {
+        let outline = "1\n  2\n  three";
+
+        let err = Tree::from_indented(outline, parse).unwrap_err();
+
+        assert!(matches!(err, ParseError::Value { line: 3, .. }));
+        assert_eq!("invalid value at line 3", err.to_string());
+    }
+}
@@ -0,0 +1,403 @@
+//! Parallel iteration over the nodes of a tree with [`rayon`].
+//!
+//! The iterators visit the entries of the arena in parallel, so the nodes are not yielded in
+//! depth-first order. To respect the structure of the tree use [`Tree::par_visit_subtrees`] and
+//! [`Tree::par_fold_bottom_up`].
+
+use rayon::prelude::*;
+
+use crate::{entry::Entry, error::Error, id::NodeId, tree::Tree};
+
+impl<T: Sync> Tree<T> {
+    /// Returns a parallel iterator over the ids and values of all the nodes, in no particular
+    /// order.
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = (NodeId, &T)> + '_ {
+        let tree = self.tag;
+
+        self.nodes
+            .par_iter()
+            .enumerate()
+            .filter_map(move |(index, entry)| match entry {
+                Entry::Free { .. } => None,
+                Entry::Occupied { generation, node } => {
+                    Some((NodeId::new(tree, index, *generation), &node.value))
+                }
+            })
+    }
+}
+
+impl<T: Send> Tree<T> {
+    /// Returns a parallel iterator over the ids and mutable values of all the nodes, in no
+    /// particular order.
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = (NodeId, &mut T)> + '_ {
+        let tree = self.tag;
+
+        self.nodes
+            .par_iter_mut()
+            .enumerate()
+            .filter_map(move |(index, entry)| match entry {
+                Entry::Free { .. } => None,
+                Entry::Occupied { generation, node } => {
+                    Some((NodeId::new(tree, index, *generation), &mut node.value))
+                }
+            })
+    }
+
+    /// Returns a parallel iterator over the mutable values of all the nodes, in no particular
+    /// order.
+    ///
+    /// ```
+    /// use forest_ds::tree::Tree;
+    /// use rayon::prelude::*;
+    ///
+    /// let mut tree = Tree::new();
+    /// tree.append_child(1);
+    /// tree.append_child(2);
+    ///
+    /// tree.par_values().for_each(|value| *value *= 10);
+    ///
+    /// assert_eq!(vec![10, 20], tree.iter().copied().collect::<Vec<_>>());
+    /// ```
+    pub fn par_values(&mut self) -> impl ParallelIterator<Item = &mut T> + '_ {
+        self.nodes.par_iter_mut().filter_map(|entry| match entry {
+            Entry::Free { .. } => None,
+            Entry::Occupied { node, .. } => Some(&mut node.value),
+        })
+    }
+}
+
+impl<T: Sync> Tree<T> {
+    /// Visits the subtree of `root` calling `f` on each node, with the subtrees of the children
+    /// of `root` visited concurrently.
+    ///
+    /// The root is visited first, then each subtree of its children is visited in depth-first
+    /// order by a single task.
+    ///
+    /// # Errors
+    ///
+    /// If the root id is invalid.
+    pub fn par_visit_subtrees<F>(&self, root: &NodeId, f: F) -> Result<(), Error>
+    where
+        F: Fn(NodeId, &T) + Sync,
+    {
+        let index = self.try_index(root, "for root")?;
+
+        f(self.node_id(index), &self.nodes[index].unwrap_ref().value);
+
+        self.children_indexes(index)
+            .into_par_iter()
+            .for_each(|child| {
+                let mut current = Some(child);
+                while let Some(node) = current {
+                    f(self.node_id(node), &self.nodes[node].unwrap_ref().value);
+
+                    current = self.next_in_subtree(child, node);
+                }
+            });
+
+        Ok(())
+    }
+
+    /// Folds the subtree of `root` from the leaves, calling `f` on each node with the results of
+    /// its children in order.
+    ///
+    /// The subtrees of the children of `root` are folded concurrently, each by a single task.
+    ///
+    /// ```
+    /// use forest_ds::tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// let root = tree.append_child(1);
+    /// tree.append_child(2);
+    /// tree.append_child(3);
+    ///
+    /// let size = tree.par_fold_bottom_up(&root, |_, _, children: Vec<usize>| {
+    ///     1 + children.iter().sum::<usize>()
+    /// });
+    ///
+    /// assert_eq!(Ok(3), size);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the root id is invalid.
+    pub fn par_fold_bottom_up<U, F>(&self, root: &NodeId, f: F) -> Result<U, Error>
+    where
+        U: Send,
+        F: Fn(NodeId, &T, Vec<U>) -> U + Sync,
+    {
+        let index = self.try_index(root, "for root")?;
+
+        let children = self
+            .children_indexes(index)
+            .into_par_iter()
+            .map(|child| self.fold_at(child, &f))
+            .collect();
+
+        Ok(f(
+            self.node_id(index),
+            &self.nodes[index].unwrap_ref().value,
+            children,
+        ))
+    }
+
+    /// Folds the subtree sequentially, keeping the pending nodes in a stack.
+    fn fold_at<U, F>(&self, index: usize, f: &F) -> U
+    where
+        F: Fn(NodeId, &T, Vec<U>) -> U,
+    {
+        // Node, next child to fold and results of the folded children
+        let mut stack = vec![(
+            index,
+            self.nodes[index].unwrap_ref().first_child,
+            Vec::new(),
+        )];
+
+        loop {
+            let (node, next, _) = stack.last_mut().expect("the stack is not empty");
+
+            match *next {
+                Some(child) => {
+                    *next = self.nodes[child].unwrap_ref().next_sibling;
+
+                    let first_child = self.nodes[child].unwrap_ref().first_child;
+                    stack.push((child, first_child, Vec::new()));
+                }
+                None => {
+                    let node = *node;
+                    let (_, _, children) = stack.pop().expect("the stack is not empty");
+
+                    let result = f(
+                        self.node_id(node),
+                        &self.nodes[node].unwrap_ref().value,
+                        children,
+                    );
+
+                    match stack.last_mut() {
+                        Some((_, _, results)) => results.push(result),
+                        None => return result,
+                    }
+                }
+            }
+        }
+    }
+
+    fn children_indexes(&self, index: usize) -> Vec<usize> {
+        let mut children = Vec::new();
+
+        let mut child = self.nodes[index].unwrap_ref().first_child;
+        while let Some(index) = child {
+            children.push(index);
+            child = self.nodes[index].unwrap_ref().next_sibling;
+        }
+
+        children
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{sync::Mutex, thread};
+
+    use rayon::prelude::*;
+
+    use crate::{error::Error, id::NodeId, iter::traverse::Edge, tree::Tree};
+    use pretty_assertions::assert_eq;
+
+    /// Chain with a leaf on each node.
+    fn deep_tree() -> (Tree<usize>, Vec<NodeId>) {
+        let mut tree = Tree::new();
+
+        let mut chain = vec![tree.append_child(0)];
+        for depth in 1..20 {
+            let parent = chain[depth - 1];
+
+            tree.append_child_to(&parent, depth * 100).unwrap();
+            chain.push(tree.append_child_to(&parent, depth).unwrap());
+        }
+
+        (tree, chain)
+    }
+
+    #[test]
+    fn should_par_iter_occupied_nodes() {
+        let mut tree = Tree::new();
+        // 1 -> 2 -> 3
+        // 4
+        let root = tree.append_child(1);
+        let removed = tree.append_child(2);
+        tree.append_child(3);
+        tree.insert_sibling_after(&root, 4).unwrap();
+
+        // Leaves a free slot between the nodes
+        tree.remove(removed).unwrap();
+
+        let mut values: Vec<i32> = tree.par_iter().map(|(_, value)| *value).collect();
+        values.sort_unstable();
+
+        assert_eq!(vec![1, 3, 4], values);
+
+        for (id, value) in tree.par_iter().collect::<Vec<_>>() {
+            assert_eq!(Some(value), tree.get(&id));
+        }
+    }
+
+    #[test]
+    fn should_par_iter_mut() {
+        let mut tree = Tree::new();
+        // 1 -> 2 -> 3
+        // 4
+        let root = tree.append_child(1);
+        let removed = tree.append_child(2);
+        tree.append_child(3);
+        tree.insert_sibling_after(&root, 4).unwrap();
+
+        // Leaves a free slot between the nodes
+        tree.remove(removed).unwrap();
+
+        let ids: Vec<_> = tree
+            .par_iter_mut()
+            .map(|(id, value)| {
+                *value += 1;
+                id
+            })
+            .collect();
+
+        let mut values: Vec<i32> = ids.iter().map(|id| *tree.get(id).unwrap()).collect();
+        values.sort_unstable();
+
+        assert_eq!(vec![2, 4, 5], values);
+    }
+
+    #[test]
+    fn should_par_transform_values() {
+        let mut tree = Tree::new();
+        // 1 -> 2 -> 3
+        // 4
+        let root = tree.append_child(1);
+        let removed = tree.append_child(2);
+        tree.append_child(3);
+        tree.insert_sibling_after(&root, 4).unwrap();
+
+        // Leaves a free slot between the nodes
+        tree.remove(removed).unwrap();
+
+        tree.par_values().for_each(|value| *value *= 2);
+
+        assert_eq!(vec![2, 6, 8], tree.iter().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_visit_parents_before_children() {
+        let (tree, chain) = deep_tree();
+
+        let visited = Mutex::new(Vec::new());
+        tree.par_visit_subtrees(&chain[0], |id, _| visited.lock().unwrap().push(id))
+            .unwrap();
+        let visited = visited.into_inner().unwrap();
+
+        assert_eq!(39, visited.len());
+
+        let position = |id: &NodeId| visited.iter().position(|visited| visited == id).unwrap();
+        for id in &visited {
+            let index = tree.index(id).unwrap();
+
+            if let Some(parent) = tree.nodes[index].unwrap_ref().parent {
+                assert!(position(&tree.node_id(parent)) < position(id));
+            }
+        }
+    }
+
+    #[test]
+    fn should_visit_each_subtree_depth_first_in_one_task() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(0);
+        let children: Vec<_> = (1..=4)
+            .map(|child| {
+                let id = tree.append_child_to(&root, child * 1000).unwrap();
+
+                let mut parent = id;
+                for depth in 1..20 {
+                    tree.append_child_to(&parent, child * 1000 + depth * 10)
+                        .unwrap();
+                    parent = tree.append_child_to(&parent, child * 1000 + depth).unwrap();
+                }
+
+                id
+            })
+            .collect();
+
+        let visited = Mutex::new(Vec::new());
+        tree.par_visit_subtrees(&root, |_, value| {
+            visited
+                .lock()
+                .unwrap()
+                .push((*value, thread::current().id()));
+        })
+        .unwrap();
+        let visited = visited.into_inner().unwrap();
+
+        assert_eq!(0, visited[0].0);
+
+        for child in &children {
+            let expected: Vec<usize> = tree
+                .traverse_from(child)
+                .filter_map(|edge| match edge {
+                    Edge::Open(id) => tree.get(&id).copied(),
+                    Edge::Close(_) => None,
+                })
+                .collect();
+
+            let subtree: Vec<_> = visited
+                .iter()
+                .filter(|(value, _)| expected.contains(value))
+                .collect();
+
+            assert_eq!(
+                expected,
+                subtree.iter().map(|(value, _)| *value).collect::<Vec<_>>()
+            );
+            assert!(subtree.iter().all(|(_, thread)| *thread == subtree[0].1));
+        }
+    }
+
+    #[test]
+    fn should_fold_bottom_up() {
+        let (tree, chain) = deep_tree();
+        let root = chain[0];
+
+        let size = tree
+            .par_fold_bottom_up(&root, |_, _, children: Vec<usize>| {
+                1 + children.iter().sum::<usize>()
+            })
+            .unwrap();
+        assert_eq!(39, size);
+
+        // The children are passed in order
+        let values = tree
+            .par_fold_bottom_up(&root, |_, value, children: Vec<Vec<usize>>| {
+                std::iter::once(*value)
+                    .chain(children.into_iter().flatten())
+                    .collect()
+            })
+            .unwrap();
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), values);
+    }
+
+    #[test]
+    fn should_reject_invalid_root() {
+        let (mut tree, chain) = deep_tree();
+        let root = chain[0];
+        tree.remove(root).unwrap();
+
+        assert_eq!(
+            Err(Error::Invalid("for root")),
+            tree.par_visit_subtrees(&root, |_, _| {})
+        );
+        assert_eq!(
+            Err(Error::Invalid("for root")),
+            tree.par_fold_bottom_up(&root, |_, _, _: Vec<()>| {})
+        );
+    }
+}
@@ -4,7 +4,9 @@ use crate::{error::Error, id::NodeId, tree::Tree};
 
 impl<T> Tree<T> {
     /// Makes three nodes related to each other
-    fn relate(
+    ///
+    /// The node must be unlinked, and the siblings must be next to each other.
+    pub(crate) fn relate(
         &mut self,
         node_index: usize,
         parent_index: Option<usize>,
@@ -12,11 +14,16 @@ impl<T> Tree<T> {
         next_sibling_index: Option<usize>,
     ) {
         let node = self.nodes[node_index].unwrap_mut();
+
+        debug_assert!(node.parent.is_none());
+        debug_assert!(node.prev_sibling.is_none());
+        debug_assert!(node.next_sibling.is_none());
+
         node.parent = parent_index;
         node.prev_sibling = prev_sibling_index;
         node.next_sibling = next_sibling_index;
 
-        // If the parent doesn't have children set the node as first and last
+        // If the node is at the start or end of the children, update the parent
         if let Some(parent_index) = parent_index {
             let parent = self.nodes[parent_index].unwrap_mut();
 
@@ -32,7 +39,7 @@ impl<T> Tree<T> {
         if let Some(prev_sibling_index) = prev_sibling_index {
             let prev_sibling = self.nodes[prev_sibling_index].unwrap_mut();
 
-            debug_assert!(prev_sibling.next_sibling.is_none());
+            debug_assert_eq!(prev_sibling.next_sibling, next_sibling_index);
             debug_assert_eq!(prev_sibling.parent, parent_index);
 
             prev_sibling.next_sibling = Some(node_index);
@@ -41,61 +48,161 @@ impl<T> Tree<T> {
         if let Some(next_sibling_index) = next_sibling_index {
             let next_sibling = self.nodes[next_sibling_index].unwrap_mut();
 
-            debug_assert!(next_sibling.prev_sibling.is_none());
+            debug_assert_eq!(next_sibling.prev_sibling, prev_sibling_index);
             debug_assert_eq!(next_sibling.parent, parent_index);
 
             next_sibling.prev_sibling = Some(node_index);
         }
+
+        // The node is placed before the first root
+        if parent_index.is_none()
+            && prev_sibling_index.is_none()
+            && next_sibling_index.is_some()
+            && next_sibling_index == self.first_node
+        {
+            self.first_node = Some(node_index);
+        }
+
+        // The node is placed right after the last node, so its subtree is now at the end. This
+        // can only happen if the node is the last of its siblings.
+        if next_sibling_index.is_none() {
+            let prev_node = prev_sibling_index
+                .map(|index| self.last_descendant(index))
+                .or(parent_index);
+
+            if prev_node.is_some() && prev_node == self.last_node {
+                self.last_node = Some(self.last_descendant(node_index));
+            }
+        }
+    }
+
+    /// Removes the node, with all its descendants, from its parent and siblings.
+    ///
+    /// The node will be left without relations, like a node created with [`Tree::create_node`].
+    pub(crate) fn unlink(&mut self, node_index: usize) {
+        let node = self.nodes[node_index].unwrap_mut();
+
+        let parent = node.parent.take();
+        let prev_sibling = node.prev_sibling.take();
+        let next_sibling = node.next_sibling.take();
+
+        // The subtree was at the end, the last node is now the one before it
+        let is_last = self.last_node.map_or(false, |last| {
+            last == node_index || self.is_ancestor(node_index, last)
+        });
+
+        if is_last {
+            self.last_node = prev_sibling
+                .map(|index| self.last_descendant(index))
+                .or(parent);
+        }
+
+        if Some(node_index) == self.first_node {
+            self.first_node = next_sibling;
+        }
+
+        if let Some(parent_index) = parent {
+            let parent = self.nodes[parent_index].unwrap_mut();
+
+            if parent.first_child == Some(node_index) {
+                parent.first_child = next_sibling;
+            }
+
+            if parent.last_child == Some(node_index) {
+                parent.last_child = prev_sibling;
+            }
+        }
+
+        if let Some(next_sibling_index) = next_sibling {
+            let next = self.nodes[next_sibling_index].unwrap_mut();
+
+            next.prev_sibling = prev_sibling;
+        }
+
+        if let Some(prev_sibling_index) = prev_sibling {
+            let prev = self.nodes[prev_sibling_index].unwrap_mut();
+
+            prev.next_sibling = next_sibling;
+        }
+    }
+
+    /// Returns the last node, in depth-first order, of the subtree of the given node.
+    pub(crate) fn last_descendant(&self, index: usize) -> usize {
+        let mut last = index;
+
+        while let Some(child) = self.nodes[last].unwrap_ref().last_child {
+            last = child;
+        }
+
+        last
+    }
+
+    /// Checks if the node at `ancestor` is one of the ancestors of the node at `index`.
+    fn is_ancestor(&self, ancestor: usize, index: usize) -> bool {
+        let mut parent = self.nodes[index].unwrap_ref().parent;
+
+        while let Some(parent_index) = parent {
+            if parent_index == ancestor {
+                return true;
+            }
+
+            parent = self.nodes[parent_index].unwrap_ref().parent;
+        }
+
+        false
     }
 
     /// Make the `child` nodes as the last child of the `parent` node.
     ///
+    /// If the `child` is already in the tree, it will be moved with all its descendants.
+    ///
     /// # Errors
     ///
     /// - Fails of the same `NodeId` is passed
-    /// - TODO: Fail if the child node is parent of the parent node.
+    /// - Fails if the child node is an ancestor of the parent node
     pub fn make_child(&mut self, child: &NodeId, parent: &NodeId) -> Result<(), Error> {
-        let child_index = self.index(child).ok_or(Error::Invalid("for child"))?;
-        let parent_index = self.index(parent).ok_or(Error::Invalid("for parent"))?;
+        let child_index = self.try_index(child, "for child")?;
+        let parent_index = self.try_index(parent, "for parent")?;
 
         if child_index == parent_index {
             return Err(Error::SameNode);
         }
 
-        // TODO: search if the child has the parent as child
+        if self.is_ancestor(child_index, parent_index) {
+            return Err(Error::WouldCreateCycle);
+        }
+
+        self.unlink(child_index);
 
         let parent_node = self.nodes[parent_index].unwrap_ref();
         let last_child = parent_node.last_child;
 
         self.relate(child_index, Some(parent_index), last_child, None);
 
-        if Some(parent_index) == self.last_node {
-            let mut cursor = self.cursor(child).unwrap();
-
-            while cursor.move_next().is_ok() {}
-
-            let id = &cursor.id();
-            self.last_node = self.index(id);
-        }
-
         Ok(())
     }
 
     /// Make the `node` as the previous sibling of `sibling`
     ///
+    /// If the `node` is already in the tree, it will be moved with all its descendants.
+    ///
     /// # Errors
     ///
     /// - Fails of the same `NodeId` is passed
-    /// - TODO: Fail if the child node is parent of the parent node.
+    /// - Fails if the node is an ancestor of the sibling
     pub fn make_prev_siblings(&mut self, node: &NodeId, sibling: &NodeId) -> Result<(), Error> {
-        let node_index = self.index(node).ok_or(Error::Invalid("for node"))?;
-        let sibling_index = self.index(sibling).ok_or(Error::Invalid("for sibling"))?;
+        let node_index = self.try_index(node, "for node")?;
+        let sibling_index = self.try_index(sibling, "for sibling")?;
 
         if node_index == sibling_index {
             return Err(Error::SameNode);
         }
 
-        // TODO: search if the child has the parent as child
+        if self.is_ancestor(node_index, sibling_index) {
+            return Err(Error::WouldCreateCycle);
+        }
+
+        self.unlink(node_index);
 
         let sibling_node = self.nodes[sibling_index].unwrap_ref();
         let parent_index = sibling_node.parent;
@@ -103,28 +210,30 @@ impl<T> Tree<T> {
 
         self.relate(node_index, parent_index, prev_sibling, Some(sibling_index));
 
-        if Some(sibling_index) == self.first_node {
-            self.first_node = Some(node_index);
-        }
-
         Ok(())
     }
 
     /// Make the `node` as the next sibling of `sibling`
     ///
+    /// If the `node` is already in the tree, it will be moved with all its descendants.
+    ///
     /// # Errors
     ///
     /// - Fails of the same `NodeId` is passed
-    /// - TODO: Fail if the child node is parent of the parent node.
+    /// - Fails if the node is an ancestor of the sibling
     pub fn make_next_siblings(&mut self, node: &NodeId, sibling: &NodeId) -> Result<(), Error> {
-        let node_index = self.index(node).ok_or(Error::Invalid("for node"))?;
-        let sibling_index = self.index(sibling).ok_or(Error::Invalid("for sibling"))?;
+        let node_index = self.try_index(node, "for node")?;
+        let sibling_index = self.try_index(sibling, "for sibling")?;
 
         if node_index == sibling_index {
             return Err(Error::SameNode);
         }
 
-        // TODO: search if the child has the parent as child
+        if self.is_ancestor(node_index, sibling_index) {
+            return Err(Error::WouldCreateCycle);
+        }
+
+        self.unlink(node_index);
 
         let sibling_node = self.nodes[sibling_index].unwrap_ref();
         let parent_index = sibling_node.parent;
@@ -132,51 +241,18 @@ impl<T> Tree<T> {
 
         self.relate(node_index, parent_index, Some(sibling_index), next_sibling);
 
-        if Some(sibling_index) == self.last_node {
-            self.last_node = Some(node_index);
-        }
-
         Ok(())
     }
 
-    /// Detach the node from it's parent
+    /// Detach the node, with all its descendants, from it's parent and siblings
     ///
     /// # Errors
     ///
     /// - Fails of the `node` was removed
     pub fn detach(&mut self, node: &NodeId) -> Result<(), Error> {
-        let node_index = self.index(node).ok_or(Error::Invalid("for node"))?;
-        let node = self.nodes[node_index].unwrap_mut();
-
-        let parent = node.parent;
-        let prev_sibling = node.prev_sibling;
-        let next_sibling = node.next_sibling;
-
-        node.parent = None;
+        let node_index = self.try_index(node, "for node")?;
 
-        if let Some(parent_index) = parent {
-            let parent = self.nodes[parent_index].unwrap_mut();
-
-            if parent.last_child == Some(node_index) {
-                parent.last_child = prev_sibling;
-            }
-
-            if parent.first_child == Some(node_index) {
-                parent.last_child = next_sibling;
-            }
-        }
-
-        if let Some(next_sibling_index) = next_sibling {
-            let next = self.nodes[next_sibling_index].unwrap_mut();
-
-            next.prev_sibling = prev_sibling;
-        }
-
-        if let Some(prev_sibling_index) = prev_sibling {
-            let prev = self.nodes[prev_sibling_index].unwrap_mut();
-
-            prev.next_sibling = next_sibling;
-        }
+        self.unlink(node_index);
 
         Ok(())
     }
@@ -184,7 +260,7 @@ impl<T> Tree<T> {
 
 #[cfg(test)]
 mod test {
-    use crate::tree::Tree;
+    use crate::{error::Error, tree::Tree};
 
     #[test]
     fn should_update_last_node_make_child() {
@@ -203,6 +279,26 @@ mod test {
         assert_eq!(Some(node), tree.last_node_id());
     }
 
+    #[test]
+    fn should_insert_after_deep_node() {
+        let mut tree = Tree::new();
+
+        // 0 -> 1 -> ... -> 9999, with the inserted siblings of 0 after it
+        let node = tree.append_child(0);
+        for value in 1..10_000 {
+            tree.append_child(value);
+        }
+        let last = tree.last_node_id();
+
+        for value in 10_000..20_000 {
+            tree.insert_sibling_after(&node, value).unwrap();
+        }
+
+        assert_eq!(tree.first_node_id(), Some(node));
+        assert_ne!(tree.last_node_id(), last);
+        assert_eq!(Some(&10_000), tree.iter().last());
+    }
+
     #[test]
     fn should_update_first_node_make_sibling() {
         let mut tree = Tree::new();
@@ -235,4 +331,140 @@ mod test {
 
         assert_eq!(Some(node), tree.last_node_id());
     }
+
+    #[test]
+    fn should_not_make_child_of_child() {
+        let mut tree = Tree::new();
+
+        let parent = tree.append_child(1);
+        let child = tree.append_child(2);
+
+        assert_eq!(
+            Err(Error::WouldCreateCycle),
+            tree.make_child(&parent, &child)
+        );
+
+        assert_eq!(vec![1, 2], tree.iter().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_not_make_child_of_descendant() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(1);
+        tree.append_child(2);
+        tree.append_sibling(3);
+        let descendant = tree.append_child(4);
+
+        assert_eq!(
+            Err(Error::WouldCreateCycle),
+            tree.make_child(&root, &descendant)
+        );
+
+        assert_eq!(vec![1, 2, 3, 4], tree.iter().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_not_make_sibling_of_descendant() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(1);
+        let child = tree.append_child(2);
+        let descendant = tree.append_child(3);
+
+        assert_eq!(
+            Err(Error::WouldCreateCycle),
+            tree.make_prev_siblings(&root, &child)
+        );
+        assert_eq!(
+            Err(Error::WouldCreateCycle),
+            tree.make_next_siblings(&root, &descendant)
+        );
+
+        assert_eq!(vec![1, 2, 3], tree.iter().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_move_child_to_other_parent() {
+        let mut tree = Tree::new();
+
+        let first = tree.append_child(1);
+        let child = tree.append_child(2);
+        tree.append_child(3);
+        let second = tree.insert_sibling_after(&first, 4).unwrap();
+        tree.append_child(5);
+
+        tree.make_child(&child, &second).unwrap();
+
+        assert_eq!(
+            vec![1, 4, 5, 2, 3],
+            tree.iter().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&3), tree.get(&tree.last_node_id().unwrap()));
+
+        let mut cursor = tree.cursor(&first).unwrap();
+        assert!(cursor.first_child().is_err());
+    }
+
+    #[test]
+    fn should_move_sibling_before_and_after() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(0);
+        let first = tree.append_child_to(&root, 1).unwrap();
+        let second = tree.append_child_to(&root, 2).unwrap();
+        let third = tree.append_child_to(&root, 3).unwrap();
+
+        tree.make_prev_siblings(&third, &first).unwrap();
+        assert_eq!(vec![0, 3, 1, 2], tree.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(second), tree.last_node_id());
+
+        tree.make_next_siblings(&third, &second).unwrap();
+        assert_eq!(vec![0, 1, 2, 3], tree.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(third), tree.last_node_id());
+
+        tree.make_next_siblings(&first, &second).unwrap();
+        assert_eq!(vec![0, 2, 1, 3], tree.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(third), tree.last_node_id());
+    }
+
+    #[test]
+    fn should_move_root_under_other_root() {
+        let mut tree = Tree::new();
+
+        let first = tree.append_child(1);
+        tree.append_child(2);
+        let second = tree.insert_sibling_after(&first, 3).unwrap();
+        let third = tree.append_sibling(4);
+
+        tree.make_child(&first, &third).unwrap();
+
+        assert_eq!(Some(second), tree.first_node_id());
+        assert_eq!(vec![3, 4, 1, 2], tree.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(&2), tree.get(&tree.last_node_id().unwrap()));
+    }
+
+    #[test]
+    fn should_detach_subtree() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(1);
+        let first = tree.append_child(2);
+        tree.append_child(3);
+        let second = tree.insert_sibling_after(&first, 4).unwrap();
+
+        tree.detach(&first).unwrap();
+
+        assert_eq!(vec![1, 4], tree.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(second), tree.last_node_id());
+        assert_eq!(
+            vec![2, 3],
+            tree.iter_from(&first).copied().collect::<Vec<_>>()
+        );
+
+        tree.detach(&second).unwrap();
+
+        assert_eq!(vec![1], tree.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(root), tree.last_node_id());
+    }
 }
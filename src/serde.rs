@@ -1,19 +1,34 @@
+use std::{fmt, marker::PhantomData};
+
 use serde::{
-    ser::{SerializeSeq, SerializeStruct},
-    Serialize,
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple},
+    Deserialize, Deserializer, Serialize, Serializer,
 };
 
-use crate::{node::Node, tree::Tree};
+use crate::{id::NodeId, node::Node, subtree::SubtreeRef, tree::Tree};
+
+use self::options::{FlattenSerializer, NodeLayout, SerializeOptions};
+
+pub mod flat;
+pub mod options;
+
+/// Fields of a serialized node
+const NODE_FIELDS: &[&str] = &["value", "children"];
+/// Fields of a serialized node id
+const NODE_ID_FIELDS: &[&str] = &["tree", "index", "generation"];
 
 /// Enum to help serialize a tree
-enum NodeSerialize<'a, T> {
+pub(crate) enum NodeSerialize<'a, T> {
     Node {
         tree: &'a Tree<T>,
         node: &'a Node<T>,
+        options: &'a SerializeOptions,
     },
     Child {
         tree: &'a Tree<T>,
         index: Option<usize>,
+        options: &'a SerializeOptions,
     },
 }
 
@@ -31,18 +46,26 @@ impl<T: Serialize> Serialize for Tree<T> {
     where
         S: serde::Serializer,
     {
-        let mut sequence = serializer.serialize_seq(None)?;
-
-        let mut current_index = self.first_node;
-        while let Some(index) = current_index {
-            let node = self.nodes[index].unwrap_ref();
-
-            sequence.serialize_element(&NodeSerialize::Node { tree: self, node })?;
-
-            current_index = node.next_sibling;
+        NodeSerialize::Child {
+            tree: self,
+            index: self.first_node,
+            options: &SerializeOptions::default(),
         }
+        .serialize(serializer)
+    }
+}
 
-        sequence.end()
+impl<T: Serialize> Serialize for SubtreeRef<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        NodeSerialize::Node {
+            tree: self.tree,
+            node: self.tree.nodes[self.index].unwrap_ref(),
+            options: &SerializeOptions::default(),
+        }
+        .serialize(serializer)
     }
 }
 
@@ -52,28 +75,75 @@ impl<T: Serialize> Serialize for NodeSerialize<'_, T> {
         S: serde::Serializer,
     {
         match self {
-            NodeSerialize::Node { tree, node } => {
-                let mut node_struct = serializer.serialize_struct("Node", 2)?;
+            NodeSerialize::Node {
+                tree,
+                node,
+                options,
+            } => {
+                let children = NodeSerialize::Child {
+                    tree,
+                    index: node.first_child,
+                    options,
+                };
+                let skip_children = options.omit_empty_children && node.first_child.is_none();
+                let len = if skip_children { 1 } else { 2 };
 
-                node_struct.serialize_field("value", &node.value)?;
-                node_struct.serialize_field(
-                    "children",
-                    &NodeSerialize::Child {
-                        tree,
-                        index: node.first_child,
-                    },
-                )?;
+                match options.layout {
+                    NodeLayout::Struct => {
+                        let mut node_struct =
+                            serializer.serialize_struct(options.node_name, len)?;
 
-                node_struct.end()
+                        node_struct.serialize_field(options.value_key, &node.value)?;
+
+                        if skip_children {
+                            node_struct.skip_field(options.children_key)?;
+                        } else {
+                            node_struct.serialize_field(options.children_key, &children)?;
+                        }
+
+                        node_struct.end()
+                    }
+                    NodeLayout::Flatten => {
+                        let mut node_map = serializer.serialize_map(None)?;
+
+                        node.value
+                            .serialize(FlattenSerializer::new(&mut node_map))?;
+
+                        if !skip_children {
+                            node_map.serialize_entry(options.children_key, &children)?;
+                        }
+
+                        node_map.end()
+                    }
+                    NodeLayout::Tuple => {
+                        let mut node_tuple = serializer.serialize_tuple(len)?;
+
+                        node_tuple.serialize_element(&node.value)?;
+
+                        if !skip_children {
+                            node_tuple.serialize_element(&children)?;
+                        }
+
+                        node_tuple.end()
+                    }
+                }
             }
-            NodeSerialize::Child { tree, index } => {
+            NodeSerialize::Child {
+                tree,
+                index,
+                options,
+            } => {
                 let mut sequence = serializer.serialize_seq(None)?;
 
                 let mut current_index = *index;
                 while let Some(index) = current_index {
                     let node = tree.nodes[index].unwrap_ref();
 
-                    sequence.serialize_element(&NodeSerialize::Node { tree, node })?;
+                    sequence.serialize_element(&NodeSerialize::Node {
+                        tree,
+                        node,
+                        options,
+                    })?;
 
                     current_index = node.next_sibling;
                 }
@@ -83,3 +153,550 @@ impl<T: Serialize> Serialize for NodeSerialize<'_, T> {
         }
     }
 }
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Tree<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(TreeVisitor(PhantomData))
+    }
+}
+
+struct TreeVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for TreeVisitor<T> {
+    type Value = Tree<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of nodes")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut tree = Tree::with_capacity(seq.size_hint().unwrap_or_default());
+        let mut last_root = None;
+
+        while let Some(index) = seq.next_element_seed(NodeSeed { tree: &mut tree })? {
+            match last_root {
+                Some(last) => tree.relate(index, None, Some(last), None),
+                None => {
+                    tree.first_node = Some(index);
+                    tree.last_node = Some(tree.last_descendant(index));
+                }
+            }
+
+            last_root = Some(index);
+        }
+
+        Ok(tree)
+    }
+}
+
+/// Deserializes a node with all its descendants into the tree.
+///
+/// Returns the index of the node, without relations with the other nodes.
+struct NodeSeed<'a, T> {
+    tree: &'a mut Tree<T>,
+}
+
+impl<T> NodeSeed<'_, T> {
+    /// Relates the children to the node
+    fn relate_children(&mut self, index: usize, children: Vec<usize>) {
+        for child in children {
+            let last_child = self.tree.nodes[index].unwrap_ref().last_child;
+
+            self.tree.relate(child, Some(index), last_child, None);
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for NodeSeed<'_, T> {
+    type Value = usize;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("Node", NODE_FIELDS, self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for NodeSeed<'_, T> {
+    type Value = usize;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a node with a value and children")
+    }
+
+    fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let value = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+
+        let index = self.tree.allocate_node(Node::new(value));
+
+        let children = seq
+            .next_element_seed(ChildrenSeed {
+                tree: &mut *self.tree,
+            })?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+        self.relate_children(index, children);
+
+        Ok(index)
+    }
+
+    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut index = None;
+        let mut children = None;
+
+        while let Some(field) = map.next_key()? {
+            match field {
+                NodeField::Value => {
+                    if index.is_some() {
+                        return Err(de::Error::duplicate_field("value"));
+                    }
+
+                    let value = map.next_value()?;
+
+                    index = Some(self.tree.allocate_node(Node::new(value)));
+                }
+                NodeField::Children => {
+                    if children.is_some() {
+                        return Err(de::Error::duplicate_field("children"));
+                    }
+
+                    children = Some(map.next_value_seed(ChildrenSeed {
+                        tree: &mut *self.tree,
+                    })?);
+                }
+                NodeField::Ignore => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let index = index.ok_or_else(|| de::Error::missing_field("value"))?;
+
+        self.relate_children(index, children.unwrap_or_default());
+
+        Ok(index)
+    }
+}
+
+/// Deserializes the children of a node.
+///
+/// Returns the indexes of the children, without relations with the other nodes.
+struct ChildrenSeed<'a, T> {
+    tree: &'a mut Tree<T>,
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for ChildrenSeed<'_, T> {
+    type Value = Vec<usize>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for ChildrenSeed<'_, T> {
+    type Value = Vec<usize>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of nodes")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut children = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+
+        while let Some(index) = seq.next_element_seed(NodeSeed {
+            tree: &mut *self.tree,
+        })? {
+            children.push(index);
+        }
+
+        Ok(children)
+    }
+}
+
+/// Field of a serialized node
+enum NodeField {
+    Value,
+    Children,
+    Ignore,
+}
+
+impl<'de> Deserialize<'de> for NodeField {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(NodeFieldVisitor)
+    }
+}
+
+struct NodeFieldVisitor;
+
+impl Visitor<'_> for NodeFieldVisitor {
+    type Value = NodeField;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a node field")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match v {
+            0 => Ok(NodeField::Value),
+            1 => Ok(NodeField::Children),
+            _ => Ok(NodeField::Ignore),
+        }
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match v {
+            "value" => Ok(NodeField::Value),
+            "children" => Ok(NodeField::Children),
+            _ => Ok(NodeField::Ignore),
+        }
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match v {
+            b"value" => Ok(NodeField::Value),
+            b"children" => Ok(NodeField::Children),
+            _ => Ok(NodeField::Ignore),
+        }
+    }
+}
+
+impl Serialize for NodeId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut id = serializer.serialize_struct("NodeId", NODE_ID_FIELDS.len())?;
+
+        id.serialize_field("tree", &self.tree)?;
+        id.serialize_field("index", &self.index)?;
+        id.serialize_field("generation", &self.generation)?;
+
+        id.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("NodeId", NODE_ID_FIELDS, NodeIdVisitor)
+    }
+}
+
+struct NodeIdVisitor;
+
+impl<'de> Visitor<'de> for NodeIdVisitor {
+    type Value = NodeId;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a node id")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let tree = next_element(&mut seq, 0, &self)?;
+        let index = next_element(&mut seq, 1, &self)?;
+        let generation = next_element(&mut seq, 2, &self)?;
+
+        Ok(NodeId::new(tree, index, generation))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut tree = None;
+        let mut index = None;
+        let mut generation = None;
+
+        while let Some(field) = map.next_key_seed(FieldSeed(NODE_ID_FIELDS))? {
+            match field {
+                Some(0) => set_field(&mut map, &mut tree, "tree")?,
+                Some(1) => set_field(&mut map, &mut index, "index")?,
+                Some(2) => set_field(&mut map, &mut generation, "generation")?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(NodeId::new(
+            tree.ok_or_else(|| de::Error::missing_field("tree"))?,
+            index.ok_or_else(|| de::Error::missing_field("index"))?,
+            generation.ok_or_else(|| de::Error::missing_field("generation"))?,
+        ))
+    }
+}
+
+/// Deserializes the index of a field, or variant, from its name or position.
+///
+/// Returns [`None`] for unknown fields.
+pub(crate) struct FieldSeed(&'static [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for FieldSeed {
+    type Value = Option<usize>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl Visitor<'_> for FieldSeed {
+    type Value = Option<usize>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "one of {:?}", self.0)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(usize::try_from(v)
+            .ok()
+            .filter(|index| *index < self.0.len()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(self.0.iter().position(|field| *field == v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(self.0.iter().position(|field| field.as_bytes() == v))
+    }
+}
+
+/// Reads the next element of a sequence, failing if it's missing.
+pub(crate) fn next_element<'de, A, V>(
+    seq: &mut A,
+    index: usize,
+    expected: &dyn de::Expected,
+) -> Result<V, A::Error>
+where
+    A: SeqAccess<'de>,
+    V: Deserialize<'de>,
+{
+    seq.next_element()?
+        .ok_or_else(|| de::Error::invalid_length(index, expected))
+}
+
+/// Sets a field of a map, failing if it was already set.
+pub(crate) fn set_field<'de, A, V>(
+    map: &mut A,
+    field: &mut Option<V>,
+    name: &'static str,
+) -> Result<(), A::Error>
+where
+    A: MapAccess<'de>,
+    V: Deserialize<'de>,
+{
+    if field.is_some() {
+        return Err(de::Error::duplicate_field(name));
+    }
+
+    *field = Some(map.next_value()?);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{error::Error, id::NodeId, tree::Tree};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_serialize() {
+        let mut tree = Tree::new();
+        // 1 -> 2 -> 4
+        //        -> 5
+        //   -> 3 -> 6
+        // 7
+        let root = tree.append_child(1);
+        let first = tree.append_child_to(&root, 2).unwrap();
+        tree.append_child(4);
+        tree.append_child_to(&first, 5).unwrap();
+        let second = tree.insert_sibling_after(&first, 3).unwrap();
+        tree.append_child_to(&second, 6).unwrap();
+        tree.insert_sibling_after(&root, 7).unwrap();
+
+        let json = serde_json::to_string(&tree).unwrap();
+
+        assert_eq!(
+            r#"[{"value":1,"children":[{"value":2,"children":[{"value":4,"children":[]},{"value":5,"children":[]}]},{"value":3,"children":[{"value":6,"children":[]}]}]},{"value":7,"children":[]}]"#,
+            json
+        );
+    }
+
+    #[test]
+    fn should_round_trip() {
+        let mut tree = Tree::new();
+        // 1 -> 2 -> 3
+        // 4
+        let root = tree.append_child(1);
+        tree.append_child(2);
+        tree.append_child(3);
+        tree.insert_sibling_after(&root, 4).unwrap();
+
+        let json = serde_json::to_string(&tree).unwrap();
+        let deserialized: Tree<i32> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            deserialized.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(json, serde_json::to_string(&deserialized).unwrap());
+        assert_eq!(
+            Some(&4),
+            deserialized.get(&deserialized.last_node_id().unwrap())
+        );
+    }
+
+    #[test]
+    fn should_deserialize_wide_forest() {
+        let json: Vec<_> = (0..40_000)
+            .map(|value| serde_json::json!({"value": value, "children": []}))
+            .collect();
+
+        let tree: Tree<i32> = serde_json::from_value(serde_json::Value::Array(json)).unwrap();
+
+        assert_eq!(
+            (0..40_000).collect::<Vec<_>>(),
+            tree.iter().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&39_999), tree.get(&tree.last_node_id().unwrap()));
+    }
+
+    #[test]
+    fn should_deserialize_fields_in_any_order() {
+        let json = r#"[
+            {"children": [{"value": 2}, {"children": [], "value": 3}], "value": 1},
+            {"value": 4, "extra": true}
+        ]"#;
+
+        let tree: Tree<i32> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(vec![1, 2, 3, 4], tree.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(&1), tree.get(&tree.first_node_id().unwrap()));
+        assert_eq!(Some(&4), tree.get(&tree.last_node_id().unwrap()));
+    }
+
+    #[test]
+    fn should_not_deserialize_without_value() {
+        let json = r#"[{"children": []}]"#;
+
+        let err = serde_json::from_str::<Tree<i32>>(json).unwrap_err();
+
+        assert!(err.to_string().contains("missing field `value`"));
+    }
+
+    #[test]
+    fn should_round_trip_node_id() {
+        let mut tree = Tree::new();
+
+        tree.append_child(1);
+        let id = tree.append_child(2);
+
+        let json = serde_json::to_string(&id).unwrap();
+        let deserialized: NodeId = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(id, deserialized);
+    }
+
+    #[test]
+    fn should_keep_node_id_valid_with_flat_tree() {
+        let mut tree = Tree::new();
+
+        tree.append_child(1);
+        let id = tree.append_child(2);
+
+        let tree_json = serde_json::to_string(&tree.as_flat()).unwrap();
+        let id_json = serde_json::to_string(&id).unwrap();
+
+        let tree: Tree<i32> =
+            super::flat::deserialize(&mut serde_json::Deserializer::from_str(&tree_json)).unwrap();
+        let id: NodeId = serde_json::from_str(&id_json).unwrap();
+
+        assert_eq!(Some(&2), tree.get(&id));
+    }
+
+    #[test]
+    fn should_reject_node_id_with_nested_tree() {
+        let mut tree = Tree::new();
+
+        tree.append_child(1);
+        let id = tree.append_child(2);
+
+        let json = serde_json::to_string(&(&tree, id)).unwrap();
+        let (mut tree, id): (Tree<i32>, NodeId) = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(None, tree.get(&id));
+        assert_eq!(Err(Error::ForeignTree), tree.append_child_to(&id, 3));
+    }
+
+    #[test]
+    fn should_serialize_subtree() {
+        let mut tree = Tree::new();
+        // 1 -> 2 -> 4
+        //        -> 5
+        //   -> 3
+        tree.append_child(1);
+        let first = tree.append_child(2);
+        tree.append_child(4);
+        tree.append_child_to(&first, 5).unwrap();
+        tree.insert_sibling_after(&first, 3).unwrap();
+
+        let first = tree.subtree(&first).unwrap();
+
+        let json = serde_json::to_string(&first).unwrap();
+
+        assert_eq!(
+            r#"{"value":2,"children":[{"value":4,"children":[]},{"value":5,"children":[]}]}"#,
+            json
+        );
+    }
+}
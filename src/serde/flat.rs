@@ -0,0 +1,824 @@
+//! Flat representation of the tree, that keeps the [`NodeId`]s valid.
+//!
+//! The arena is serialized as is, including the free entries and the tag of the tree. It can be
+//! used with `#[serde(with = "forest_ds::serde::flat")]` or with [`Tree::as_flat`].
+//!
+//! On deserialization the relations between the nodes are validated, to reject corrupted input.
+//!
+//! [`NodeId`]: crate::id::NodeId
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, EnumAccess, IgnoredAny, MapAccess, SeqAccess, VariantAccess, Visitor},
+    ser::{SerializeSeq, SerializeStruct, SerializeStructVariant},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::{next_element, set_field, FieldSeed};
+use crate::{entry::Entry, node::Node, tree::Tree};
+
+const TREE_FIELDS: &[&str] = &["tag", "first_free", "first_node", "last_node", "nodes"];
+const ENTRY_VARIANTS: &[&str] = &["Free", "Occupied"];
+const FREE_FIELDS: &[&str] = &["next_free", "generation"];
+const OCCUPIED_FIELDS: &[&str] = &[
+    "generation",
+    "value",
+    "parent",
+    "prev_sibling",
+    "next_sibling",
+    "first_child",
+    "last_child",
+];
+
+/// Serializes the tree in the flat representation.
+///
+/// # Errors
+///
+/// Fails if a value can't be serialized.
+pub fn serialize<T, S>(tree: &Tree<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    tree.as_flat().serialize(serializer)
+}
+
+/// Deserializes the tree from the flat representation.
+///
+/// # Errors
+///
+/// Fails if the input is invalid, or the relations between the nodes are corrupted.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Tree<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_struct("Tree", TREE_FIELDS, TreeVisitor(PhantomData))
+}
+
+/// Serializes the tree in the flat representation.
+#[derive(Debug, Clone, Copy)]
+pub struct Flat<'a, T> {
+    tree: &'a Tree<T>,
+}
+
+impl<T> Tree<T> {
+    /// Returns a wrapper to serialize the tree in the [flat representation](crate::serde::flat).
+    #[must_use]
+    pub fn as_flat(&self) -> Flat<'_, T> {
+        Flat { tree: self }
+    }
+}
+
+impl<T: Serialize> Serialize for Flat<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut tree = serializer.serialize_struct("Tree", TREE_FIELDS.len())?;
+
+        tree.serialize_field("tag", &self.tree.tag)?;
+        tree.serialize_field("first_free", &self.tree.first_free)?;
+        tree.serialize_field("first_node", &self.tree.first_node)?;
+        tree.serialize_field("last_node", &self.tree.last_node)?;
+        tree.serialize_field("nodes", &FlatNodes(&self.tree.nodes))?;
+
+        tree.end()
+    }
+}
+
+struct FlatNodes<'a, T>(&'a [Entry<T>]);
+
+impl<T: Serialize> Serialize for FlatNodes<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut sequence = serializer.serialize_seq(Some(self.0.len()))?;
+
+        for entry in self.0 {
+            sequence.serialize_element(&FlatEntry(entry))?;
+        }
+
+        sequence.end()
+    }
+}
+
+struct FlatEntry<'a, T>(&'a Entry<T>);
+
+impl<T: Serialize> Serialize for FlatEntry<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.0 {
+            Entry::Free {
+                next_free,
+                generation,
+            } => {
+                let mut entry =
+                    serializer.serialize_struct_variant("Entry", 0, "Free", FREE_FIELDS.len())?;
+
+                entry.serialize_field("next_free", next_free)?;
+                entry.serialize_field("generation", generation)?;
+
+                entry.end()
+            }
+            Entry::Occupied { generation, node } => {
+                let mut entry = serializer.serialize_struct_variant(
+                    "Entry",
+                    1,
+                    "Occupied",
+                    OCCUPIED_FIELDS.len(),
+                )?;
+
+                entry.serialize_field("generation", generation)?;
+                entry.serialize_field("value", &node.value)?;
+                entry.serialize_field("parent", &node.parent)?;
+                entry.serialize_field("prev_sibling", &node.prev_sibling)?;
+                entry.serialize_field("next_sibling", &node.next_sibling)?;
+                entry.serialize_field("first_child", &node.first_child)?;
+                entry.serialize_field("last_child", &node.last_child)?;
+
+                entry.end()
+            }
+        }
+    }
+}
+
+struct TreeVisitor<T>(PhantomData<T>);
+
+impl<T> TreeVisitor<T> {
+    fn build<E>(
+        tag: u64,
+        first_free: Option<usize>,
+        first_node: Option<usize>,
+        last_node: Option<usize>,
+        nodes: Vec<DeserializeEntry<T>>,
+    ) -> Result<Tree<T>, E>
+    where
+        E: de::Error,
+    {
+        let tree = Tree {
+            tag,
+            first_free,
+            first_node,
+            last_node,
+            nodes: nodes.into_iter().map(|entry| entry.0).collect(),
+        };
+
+        validate(&tree).map_err(E::custom)?;
+
+        Ok(tree)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for TreeVisitor<T> {
+    type Value = Tree<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a flat tree")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let tag = next_element(&mut seq, 0, &self)?;
+        let first_free = next_element(&mut seq, 1, &self)?;
+        let first_node = next_element(&mut seq, 2, &self)?;
+        let last_node = next_element(&mut seq, 3, &self)?;
+        let nodes = next_element(&mut seq, 4, &self)?;
+
+        Self::build(tag, first_free, first_node, last_node, nodes)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut tag = None;
+        let mut first_free = None;
+        let mut first_node = None;
+        let mut last_node = None;
+        let mut nodes = None;
+
+        while let Some(field) = map.next_key_seed(FieldSeed(TREE_FIELDS))? {
+            match field {
+                Some(0) => set_field(&mut map, &mut tag, "tag")?,
+                Some(1) => set_field(&mut map, &mut first_free, "first_free")?,
+                Some(2) => set_field(&mut map, &mut first_node, "first_node")?,
+                Some(3) => set_field(&mut map, &mut last_node, "last_node")?,
+                Some(4) => set_field(&mut map, &mut nodes, "nodes")?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Self::build(
+            tag.ok_or_else(|| de::Error::missing_field("tag"))?,
+            first_free.ok_or_else(|| de::Error::missing_field("first_free"))?,
+            first_node.ok_or_else(|| de::Error::missing_field("first_node"))?,
+            last_node.ok_or_else(|| de::Error::missing_field("last_node"))?,
+            nodes.ok_or_else(|| de::Error::missing_field("nodes"))?,
+        )
+    }
+}
+
+/// Deserializes an [`Entry`] from the flat representation.
+struct DeserializeEntry<T>(Entry<T>);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for DeserializeEntry<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_enum("Entry", ENTRY_VARIANTS, EntryVisitor(PhantomData))
+    }
+}
+
+struct EntryVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for EntryVisitor<T> {
+    type Value = DeserializeEntry<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a free or occupied entry")
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        match data.variant_seed(FieldSeed(ENTRY_VARIANTS))? {
+            (Some(0), variant) => variant.struct_variant(FREE_FIELDS, FreeVisitor(PhantomData)),
+            (Some(1), variant) => {
+                variant.struct_variant(OCCUPIED_FIELDS, OccupiedVisitor(PhantomData))
+            }
+            _ => Err(de::Error::custom(format_args!(
+                "unknown variant, expected one of {:?}",
+                ENTRY_VARIANTS
+            ))),
+        }
+    }
+}
+
+struct FreeVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for FreeVisitor<T> {
+    type Value = DeserializeEntry<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a free entry")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let next_free = next_element(&mut seq, 0, &self)?;
+        let generation = next_element(&mut seq, 1, &self)?;
+
+        Ok(DeserializeEntry(Entry::Free {
+            next_free,
+            generation,
+        }))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut next_free = None;
+        let mut generation = None;
+
+        while let Some(field) = map.next_key_seed(FieldSeed(FREE_FIELDS))? {
+            match field {
+                Some(0) => set_field(&mut map, &mut next_free, "next_free")?,
+                Some(1) => set_field(&mut map, &mut generation, "generation")?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(DeserializeEntry(Entry::Free {
+            next_free: next_free.ok_or_else(|| de::Error::missing_field("next_free"))?,
+            generation: generation.ok_or_else(|| de::Error::missing_field("generation"))?,
+        }))
+    }
+}
+
+struct OccupiedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for OccupiedVisitor<T> {
+    type Value = DeserializeEntry<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an occupied entry")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let generation = next_element(&mut seq, 0, &self)?;
+        let node = Node {
+            value: next_element(&mut seq, 1, &self)?,
+            parent: next_element(&mut seq, 2, &self)?,
+            prev_sibling: next_element(&mut seq, 3, &self)?,
+            next_sibling: next_element(&mut seq, 4, &self)?,
+            first_child: next_element(&mut seq, 5, &self)?,
+            last_child: next_element(&mut seq, 6, &self)?,
+        };
+
+        Ok(DeserializeEntry(Entry::Occupied { generation, node }))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut generation = None;
+        let mut value = None;
+        let mut parent = None;
+        let mut prev_sibling = None;
+        let mut next_sibling = None;
+        let mut first_child = None;
+        let mut last_child = None;
+
+        while let Some(field) = map.next_key_seed(FieldSeed(OCCUPIED_FIELDS))? {
+            match field {
+                Some(0) => set_field(&mut map, &mut generation, "generation")?,
+                Some(1) => set_field(&mut map, &mut value, "value")?,
+                Some(2) => set_field(&mut map, &mut parent, "parent")?,
+                Some(3) => set_field(&mut map, &mut prev_sibling, "prev_sibling")?,
+                Some(4) => set_field(&mut map, &mut next_sibling, "next_sibling")?,
+                Some(5) => set_field(&mut map, &mut first_child, "first_child")?,
+                Some(6) => set_field(&mut map, &mut last_child, "last_child")?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let generation = generation.ok_or_else(|| de::Error::missing_field("generation"))?;
+        let node = Node {
+            value: value.ok_or_else(|| de::Error::missing_field("value"))?,
+            parent: parent.ok_or_else(|| de::Error::missing_field("parent"))?,
+            prev_sibling: prev_sibling.ok_or_else(|| de::Error::missing_field("prev_sibling"))?,
+            next_sibling: next_sibling.ok_or_else(|| de::Error::missing_field("next_sibling"))?,
+            first_child: first_child.ok_or_else(|| de::Error::missing_field("first_child"))?,
+            last_child: last_child.ok_or_else(|| de::Error::missing_field("last_child"))?,
+        };
+
+        Ok(DeserializeEntry(Entry::Occupied { generation, node }))
+    }
+}
+
+/// Checks the relations between the nodes and the free entries of the tree.
+fn validate<T>(tree: &Tree<T>) -> Result<(), String> {
+    let nodes = &tree.nodes;
+    let len = nodes.len();
+
+    // Returns the linked node, checking it's valid
+    let link = |name: &str, owner: Option<usize>, link: Option<usize>| {
+        let owner = owner
+            .map(|index| format!(" of node {}", index))
+            .unwrap_or_default();
+
+        match link {
+            Some(link) if link >= len => {
+                Err(format!("{} {}{} is out of bounds", name, link, owner))
+            }
+            Some(link) if !nodes[link].is_node() => {
+                Err(format!("{} {}{} is a free entry", name, link, owner))
+            }
+            Some(link) => Ok(Some(nodes[link].unwrap_ref())),
+            None => Ok(None),
+        }
+    };
+
+    // Check all the links before following them
+    for (index, entry) in nodes.iter().enumerate() {
+        if let Entry::Occupied { node, .. } = entry {
+            link("parent", Some(index), node.parent)?;
+            link("previous sibling", Some(index), node.prev_sibling)?;
+            link("next sibling", Some(index), node.next_sibling)?;
+            link("first child", Some(index), node.first_child)?;
+            link("last child", Some(index), node.last_child)?;
+        }
+    }
+
+    let mut children = 0;
+
+    for (index, entry) in nodes.iter().enumerate() {
+        let node = match entry {
+            Entry::Free { .. } => continue,
+            Entry::Occupied { node, .. } => node,
+        };
+
+        if let Some(parent) = link("parent", Some(index), node.parent)? {
+            children += 1;
+
+            if node.prev_sibling.is_none() && parent.first_child != Some(index) {
+                return Err(format!(
+                    "node {} has no previous sibling, but it's not the first child of its parent",
+                    index
+                ));
+            }
+
+            if node.next_sibling.is_none() && parent.last_child != Some(index) {
+                return Err(format!(
+                    "node {} has no next sibling, but it's not the last child of its parent",
+                    index
+                ));
+            }
+        }
+
+        if let Some(prev) = link("previous sibling", Some(index), node.prev_sibling)? {
+            if prev.next_sibling != Some(index) || prev.parent != node.parent {
+                return Err(format!(
+                    "previous sibling of node {} is not related to it",
+                    index
+                ));
+            }
+        }
+
+        if let Some(next) = link("next sibling", Some(index), node.next_sibling)? {
+            if next.prev_sibling != Some(index) || next.parent != node.parent {
+                return Err(format!(
+                    "next sibling of node {} is not related to it",
+                    index
+                ));
+            }
+        }
+
+        let first_child = link("first child", Some(index), node.first_child)?;
+        let last_child = link("last child", Some(index), node.last_child)?;
+
+        let related = match (first_child, last_child) {
+            (Some(first), Some(last)) => {
+                first.parent == Some(index)
+                    && first.prev_sibling.is_none()
+                    && last.parent == Some(index)
+                    && last.next_sibling.is_none()
+            }
+            (None, None) => true,
+            _ => false,
+        };
+
+        if !related {
+            return Err(format!(
+                "first and last child of node {} are not related to it",
+                index
+            ));
+        }
+    }
+
+    let mut reachable_children = 0;
+
+    for (index, entry) in nodes.iter().enumerate() {
+        let node = match entry {
+            Entry::Free { .. } => continue,
+            Entry::Occupied { node, .. } => node,
+        };
+
+        let mut child = node.first_child;
+        let mut steps = 0;
+        while let Some(child_index) = child {
+            if steps >= len {
+                return Err(format!("children of node {} form a cycle", index));
+            }
+
+            steps += 1;
+            child = nodes[child_index].unwrap_ref().next_sibling;
+        }
+
+        reachable_children += steps;
+    }
+
+    if children != reachable_children {
+        return Err("some nodes are not in the children of their parent".to_string());
+    }
+
+    // Walk down from the nodes without a parent, the nodes not reached have a cycle in their
+    // parents
+    let mut visited = vec![false; len];
+    for (index, entry) in nodes.iter().enumerate() {
+        match entry {
+            Entry::Occupied { node, .. } if node.parent.is_none() => {}
+            _ => continue,
+        }
+
+        let mut current = Some(index);
+        while let Some(node) = current {
+            if visited[node] {
+                return Err(format!("node {} is reached more than once", node));
+            }
+
+            visited[node] = true;
+            current = tree.next_in_subtree(index, node);
+        }
+    }
+
+    if let Some(index) = (0..len).find(|index| nodes[*index].is_node() && !visited[*index]) {
+        return Err(format!("parents of node {} form a cycle", index));
+    }
+
+    if let Some(first) = link("first node", None, tree.first_node)? {
+        if first.parent.is_some() || first.prev_sibling.is_some() {
+            return Err("first node is not the first top-level node".to_string());
+        }
+    }
+
+    link("last node", None, tree.last_node)?;
+
+    let last_node = tree.last_root().map(|index| tree.last_descendant(index));
+    if tree.last_node != last_node {
+        return Err("last node is not the last node of the tree".to_string());
+    }
+
+    let free_entries = nodes.iter().filter(|entry| !entry.is_node()).count();
+
+    let mut in_free_list = vec![false; len];
+    let mut free = tree.first_free;
+    let mut steps = 0;
+    while let Some(index) = free {
+        if steps >= free_entries {
+            return Err("free list contains a cycle".to_string());
+        }
+
+        free = match nodes.get(index) {
+            Some(Entry::Free { next_free, .. }) => *next_free,
+            Some(Entry::Occupied { .. }) => {
+                return Err(format!("free list contains the node {}", index))
+            }
+            None => return Err(format!("free entry {} is out of bounds", index)),
+        };
+
+        in_free_list[index] = true;
+        steps += 1;
+    }
+
+    // Only the retired entries, with the last generation, are not in the free list
+    let missing = nodes.iter().enumerate().any(|(index, entry)| match entry {
+        Entry::Free { generation, .. } => *generation != u32::MAX && !in_free_list[index],
+        Entry::Occupied { .. } => false,
+    });
+
+    if missing {
+        return Err("some free entries are not in the free list".to_string());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{entry::Entry, tree::Tree};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_keep_ids_on_round_trip() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(1);
+        let removed = tree.append_child(2);
+        let child = tree.append_child(3);
+        let sibling = tree.insert_sibling_after(&root, 4).unwrap();
+
+        tree.remove(removed).unwrap();
+
+        let json = super::serialize(&tree, serde_json::value::Serializer).unwrap();
+        let deserialized: Tree<i32> = super::deserialize(json).unwrap();
+
+        assert_eq!(
+            vec![1, 3, 4],
+            deserialized.iter().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&3), deserialized.get(&child));
+        assert_eq!(Some(&4), deserialized.get(&sibling));
+        assert_eq!(None, deserialized.get(&removed));
+        assert_eq!(tree.first_free, deserialized.first_free);
+        assert_eq!(tree.nodes, deserialized.nodes);
+    }
+
+    #[test]
+    fn should_serialize_free_entries() {
+        let mut tree = Tree::new();
+
+        let removed = tree.append_child(1);
+        tree.append_child(2);
+
+        tree.remove(removed).unwrap();
+
+        let json = serde_json::to_value(tree.as_flat()).unwrap();
+
+        assert_eq!(
+            serde_json::json!([
+                {"Free": {"next_free": null, "generation": 1}},
+                {"Occupied": {
+                    "generation": 0,
+                    "value": 2,
+                    "parent": null,
+                    "prev_sibling": null,
+                    "next_sibling": null,
+                    "first_child": null,
+                    "last_child": null
+                }}
+            ]),
+            json["nodes"]
+        );
+        assert_eq!(serde_json::json!(0), json["first_free"]);
+        assert_eq!(serde_json::json!(1), json["first_node"]);
+        assert_eq!(serde_json::json!(1), json["last_node"]);
+    }
+
+    fn deserialize_error(json: serde_json::Value) -> String {
+        super::deserialize::<i32, _>(json).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn should_reject_corrupted_links() {
+        let occupied = |parent: Option<usize>, first_child: Option<usize>| {
+            serde_json::json!({"Occupied": {
+                "generation": 0,
+                "value": 0,
+                "parent": parent,
+                "prev_sibling": null,
+                "next_sibling": null,
+                "first_child": first_child,
+                "last_child": first_child,
+            }})
+        };
+
+        let out_of_bounds = serde_json::json!({
+            "tag": 0,
+            "first_free": null,
+            "first_node": 0,
+            "last_node": 0,
+            "nodes": [occupied(Some(5), None)],
+        });
+        assert_eq!(
+            "parent 5 of node 0 is out of bounds",
+            deserialize_error(out_of_bounds)
+        );
+
+        let cycle = serde_json::json!({
+            "tag": 0,
+            "first_free": null,
+            "first_node": null,
+            "last_node": null,
+            "nodes": [occupied(Some(1), Some(1)), occupied(Some(0), Some(0))],
+        });
+        assert_eq!("parents of node 0 form a cycle", deserialize_error(cycle));
+
+        let linked = |links: [Option<usize>; 5]| {
+            let [parent, prev_sibling, next_sibling, first_child, last_child] = links;
+
+            serde_json::json!({"Occupied": {
+                "generation": 0,
+                "value": 0,
+                "parent": parent,
+                "prev_sibling": prev_sibling,
+                "next_sibling": next_sibling,
+                "first_child": first_child,
+                "last_child": last_child,
+            }})
+        };
+
+        let sibling_out_of_bounds = serde_json::json!({
+            "tag": 0,
+            "first_free": null,
+            "first_node": 0,
+            "last_node": 2,
+            "nodes": [
+                linked([None, None, None, Some(1), Some(2)]),
+                linked([Some(0), None, Some(99), None, None]),
+                linked([Some(0), Some(1), None, None, None]),
+            ],
+        });
+        assert_eq!(
+            "next sibling 99 of node 1 is out of bounds",
+            deserialize_error(sibling_out_of_bounds)
+        );
+
+        let parent_out_of_bounds = serde_json::json!({
+            "tag": 0,
+            "first_free": null,
+            "first_node": null,
+            "last_node": null,
+            "nodes": [occupied(Some(1), None), occupied(Some(99), None)],
+        });
+        assert_eq!(
+            "parent 99 of node 1 is out of bounds",
+            deserialize_error(parent_out_of_bounds)
+        );
+
+        let siblings_cycle = serde_json::json!({
+            "tag": 0,
+            "first_free": null,
+            "first_node": 0,
+            "last_node": 2,
+            "nodes": [
+                linked([None, None, None, Some(1), Some(2)]),
+                linked([Some(0), None, Some(3), None, None]),
+                linked([Some(0), Some(3), None, None, None]),
+                linked([Some(0), Some(1), Some(1), None, None]),
+            ],
+        });
+        assert_eq!(
+            "previous sibling of node 2 is not related to it",
+            deserialize_error(siblings_cycle)
+        );
+
+        let not_child = serde_json::json!({
+            "tag": 0,
+            "first_free": null,
+            "first_node": 0,
+            "last_node": 0,
+            "nodes": [occupied(None, None), occupied(Some(0), None)],
+        });
+        assert_eq!(
+            "node 1 has no previous sibling, but it's not the first child of its parent",
+            deserialize_error(not_child)
+        );
+
+        let last_node = serde_json::json!({
+            "tag": 0,
+            "first_free": null,
+            "first_node": 0,
+            "last_node": 0,
+            "nodes": [occupied(None, Some(1)), occupied(Some(0), None)],
+        });
+        assert_eq!(
+            "last node is not the last node of the tree",
+            deserialize_error(last_node)
+        );
+    }
+
+    #[test]
+    fn should_validate_deep_tree() {
+        let mut tree = Tree::new();
+        for value in 0..100_000 {
+            tree.append_child(value);
+        }
+
+        let json = super::serialize(&tree, serde_json::value::Serializer).unwrap();
+        let deserialized: Tree<i32> = super::deserialize(json).unwrap();
+
+        assert_eq!(Some(&99_999), deserialized.iter().last());
+    }
+
+    #[test]
+    fn should_keep_retired_entries() {
+        let mut tree = Tree::new();
+
+        let removed = tree.append_child(1);
+        tree.insert_sibling_after(&removed, 2).unwrap();
+        tree.remove(removed).unwrap();
+
+        // Retire the slot of the removed node
+        tree.nodes[0] = Entry::Free {
+            next_free: None,
+            generation: u32::MAX,
+        };
+        tree.first_free = None;
+
+        let json = super::serialize(&tree, serde_json::value::Serializer).unwrap();
+        let deserialized: Tree<i32> = super::deserialize(json).unwrap();
+
+        assert_eq!(None, deserialized.first_free);
+        assert_eq!(None, deserialized.get(&removed));
+        assert_eq!(vec![2], deserialized.iter().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_reject_corrupted_free_list() {
+        let free = |next_free: Option<usize>| serde_json::json!({"Free": {"next_free": next_free, "generation": 1}});
+
+        let cycle = serde_json::json!({
+            "tag": 0,
+            "first_free": 0,
+            "first_node": null,
+            "last_node": null,
+            "nodes": [free(Some(1)), free(Some(0))],
+        });
+        assert_eq!("free list contains a cycle", deserialize_error(cycle));
+
+        let missing = serde_json::json!({
+            "tag": 0,
+            "first_free": 1,
+            "first_node": null,
+            "last_node": null,
+            "nodes": [free(None), free(None)],
+        });
+        assert_eq!(
+            "some free entries are not in the free list",
+            deserialize_error(missing)
+        );
+    }
+}
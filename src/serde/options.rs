@@ -0,0 +1,469 @@
+//! Options to change the nested representation of the tree.
+
+use std::fmt::Display;
+
+use serde::{
+    ser::{self, Impossible, SerializeMap, SerializeStruct},
+    Serialize, Serializer,
+};
+
+use super::NodeSerialize;
+use crate::tree::Tree;
+
+/// How each node is serialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeLayout {
+    /// A struct with the value and the children fields.
+    Struct,
+    /// A map with the fields of the value, and the children field.
+    ///
+    /// The value must serialize as a struct or a map.
+    Flatten,
+    /// A tuple with the value and the children.
+    Tuple,
+}
+
+impl Default for NodeLayout {
+    fn default() -> Self {
+        NodeLayout::Struct
+    }
+}
+
+/// Options for the serialization of the tree.
+///
+/// The default options serialize the tree like the [`Serialize`] implementation of [`Tree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializeOptions {
+    pub(crate) node_name: &'static str,
+    pub(crate) value_key: &'static str,
+    pub(crate) children_key: &'static str,
+    pub(crate) omit_empty_children: bool,
+    pub(crate) layout: NodeLayout,
+}
+
+impl SerializeOptions {
+    /// Create the default options.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name of the struct of a node.
+    #[must_use]
+    pub fn node_name(mut self, name: &'static str) -> Self {
+        self.node_name = name;
+        self
+    }
+
+    /// Name of the field with the value of a node.
+    #[must_use]
+    pub fn value_key(mut self, key: &'static str) -> Self {
+        self.value_key = key;
+        self
+    }
+
+    /// Name of the field with the children of a node.
+    #[must_use]
+    pub fn children_key(mut self, key: &'static str) -> Self {
+        self.children_key = key;
+        self
+    }
+
+    /// Skip the children of the nodes without children.
+    #[must_use]
+    pub fn omit_empty_children(mut self, omit: bool) -> Self {
+        self.omit_empty_children = omit;
+        self
+    }
+
+    /// Layout of the serialized nodes.
+    #[must_use]
+    pub fn layout(mut self, layout: NodeLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        Self {
+            node_name: "Node",
+            value_key: "value",
+            children_key: "children",
+            omit_empty_children: false,
+            layout: NodeLayout::default(),
+        }
+    }
+}
+
+/// Serializes the tree with the given [`SerializeOptions`].
+#[derive(Debug, Clone, Copy)]
+pub struct WithOptions<'a, T> {
+    tree: &'a Tree<T>,
+    options: SerializeOptions,
+}
+
+impl<T> Tree<T> {
+    /// Returns a wrapper to serialize the tree with the given options.
+    #[must_use]
+    pub fn serialize_with(&self, options: SerializeOptions) -> WithOptions<'_, T> {
+        WithOptions {
+            tree: self,
+            options,
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for WithOptions<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        NodeSerialize::Child {
+            tree: self.tree,
+            index: self.tree.first_node,
+            options: &self.options,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Serializes the fields of a struct or map in another map.
+pub(crate) struct FlattenSerializer<'a, M> {
+    map: &'a mut M,
+}
+
+impl<'a, M> FlattenSerializer<'a, M> {
+    pub(crate) fn new(map: &'a mut M) -> Self {
+        Self { map }
+    }
+}
+
+fn unsupported<E: ser::Error>() -> E {
+    E::custom("can only flatten a value serialized as a struct or a map")
+}
+
+impl<'a, M> Serializer for FlattenSerializer<'a, M>
+where
+    M: SerializeMap,
+{
+    type Ok = ();
+    type Error = M::Error;
+
+    type SerializeSeq = Impossible<(), M::Error>;
+    type SerializeTuple = Impossible<(), M::Error>;
+    type SerializeTupleStruct = Impossible<(), M::Error>;
+    type SerializeTupleVariant = Impossible<(), M::Error>;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), M::Error>;
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_some<V>(self, value: &V) -> Result<Self::Ok, Self::Error>
+    where
+        V: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        value: &V,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        V: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<V>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &V,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        V: ?Sized + Serialize,
+    {
+        Err(unsupported())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn collect_str<V>(self, _value: &V) -> Result<Self::Ok, Self::Error>
+    where
+        V: ?Sized + Display,
+    {
+        Err(unsupported())
+    }
+}
+
+impl<M> SerializeMap for FlattenSerializer<'_, M>
+where
+    M: SerializeMap,
+{
+    type Ok = ();
+    type Error = M::Error;
+
+    fn serialize_key<K>(&mut self, key: &K) -> Result<(), Self::Error>
+    where
+        K: ?Sized + Serialize,
+    {
+        self.map.serialize_key(key)
+    }
+
+    fn serialize_value<V>(&mut self, value: &V) -> Result<(), Self::Error>
+    where
+        V: ?Sized + Serialize,
+    {
+        self.map.serialize_value(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl<M> SerializeStruct for FlattenSerializer<'_, M>
+where
+    M: SerializeMap,
+{
+    type Ok = ();
+    type Error = M::Error;
+
+    fn serialize_field<V>(&mut self, key: &'static str, value: &V) -> Result<(), Self::Error>
+    where
+        V: ?Sized + Serialize,
+    {
+        self.map.serialize_entry(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use crate::tree::Tree;
+    use pretty_assertions::assert_eq;
+
+    use super::{NodeLayout, SerializeOptions};
+
+    #[test]
+    fn should_serialize_with_default_options() {
+        let mut tree = Tree::new();
+        // 1 -> 2
+        // 3
+        let root = tree.append_child(1);
+        tree.append_child(2);
+        tree.insert_sibling_after(&root, 3).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&tree).unwrap(),
+            serde_json::to_string(&tree.serialize_with(SerializeOptions::new())).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_rename_fields() {
+        let mut tree = Tree::new();
+        // 1 -> 2
+        // 3
+        let root = tree.append_child(1);
+        tree.append_child(2);
+        tree.insert_sibling_after(&root, 3).unwrap();
+
+        let options = SerializeOptions::new()
+            .value_key("name")
+            .children_key("nodes")
+            .omit_empty_children(true);
+
+        let json = serde_json::to_string(&tree.serialize_with(options)).unwrap();
+
+        assert_eq!(r#"[{"name":1,"nodes":[{"name":2}]},{"name":3}]"#, json);
+    }
+
+    #[test]
+    fn should_serialize_as_tuples() {
+        let mut tree = Tree::new();
+        // 1 -> 2
+        // 3
+        let root = tree.append_child(1);
+        tree.append_child(2);
+        tree.insert_sibling_after(&root, 3).unwrap();
+
+        let options = SerializeOptions::new().layout(NodeLayout::Tuple);
+
+        let json = serde_json::to_string(&tree.serialize_with(options)).unwrap();
+        assert_eq!(r#"[[1,[[2,[]]]],[3,[]]]"#, json);
+
+        let options = options.omit_empty_children(true);
+
+        let json = serde_json::to_string(&tree.serialize_with(options)).unwrap();
+        assert_eq!(r#"[[1,[[2]]],[3]]"#, json);
+    }
+
+    #[test]
+    fn should_flatten_value() {
+        let mut tree = Tree::new();
+
+        let value = |name: &str| BTreeMap::from([("name", name.to_string())]);
+
+        let root = tree.append_child(value("root"));
+        tree.append_child(value("child"));
+        tree.insert_sibling_after(&root, value("sibling")).unwrap();
+
+        let options = SerializeOptions::new()
+            .layout(NodeLayout::Flatten)
+            .omit_empty_children(true);
+
+        let json = serde_json::to_string(&tree.serialize_with(options)).unwrap();
+
+        assert_eq!(
+            r#"[{"name":"root","children":[{"name":"child"}]},{"name":"sibling"}]"#,
+            json
+        );
+    }
+
+    #[test]
+    fn should_not_flatten_primitive() {
+        let mut tree = Tree::new();
+        tree.append_child(1);
+
+        let options = SerializeOptions::new().layout(NodeLayout::Flatten);
+
+        let err = serde_json::to_string(&tree.serialize_with(options)).unwrap_err();
+
+        assert_eq!(
+            "can only flatten a value serialized as a struct or a map",
+            err.to_string()
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! Conversion between trees and S-expressions.
+//!
+//! A node with children is a list starting with its value, followed by its children, while a
+//! node without children is a single atom. The roots are written one per line:
+//!
+//! ```text
+//! (a (b c) d)
+//! e
+//! ```
+//!
+//! The first line is a root `a` with the children `b` and `d`, where `b` has the child `c`, and
+//! the second line is another root `e`. Atoms with whitespace, parentheses, quotes or
+//! backslashes are written between quotes.
+
+use std::{iter::Peekable, str::Chars};
+
+use crate::{error::ParseError, id::NodeId, tree::Tree};
+
+impl<T> Tree<T> {
+    /// Parses a tree from S-expressions.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the line number if the expressions are malformed, or a value can't be parsed.
+    pub fn from_sexpr<F, E>(input: &str, mut parse: F) -> Result<Self, ParseError<E>>
+    where
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        let mut tree = Tree::new();
+        let mut last_root: Option<NodeId> = None;
+        // Value of each open list, None until the first atom is read
+        let mut lists: Vec<(usize, Option<NodeId>)> = Vec::new();
+
+        let mut tokens = Tokens::new(input);
+
+        while let Some((line, token)) = tokens.next_token() {
+            let syntax = |reason| ParseError::Syntax { line, reason };
+
+            match token? {
+                Token::Open => {
+                    if let Some((_, None)) = lists.last() {
+                        return Err(syntax("expected an atom"));
+                    }
+
+                    lists.push((line, None));
+                }
+                Token::Close => match lists.pop() {
+                    None => return Err(syntax("unexpected closing parenthesis")),
+                    Some((_, None)) => return Err(syntax("empty list")),
+                    Some((_, Some(_))) => {}
+                },
+                Token::Atom(atom) => {
+                    let value = parse(&atom).map_err(|error| ParseError::Value { line, error })?;
+
+                    // The first atom of a list is the value of the node
+                    let (parent, head) = match lists.split_last_mut() {
+                        Some(((_, head @ None), rest)) => {
+                            (rest.last().and_then(|(_, parent)| *parent), Some(head))
+                        }
+                        Some(((_, parent), _)) => (*parent, None),
+                        None => (None, None),
+                    };
+
+                    let id = match parent {
+                        Some(parent) => tree
+                            .append_child_to(&parent, value)
+                            .expect("the node is in the tree"),
+                        None => {
+                            let id = match last_root {
+                                Some(root) => tree
+                                    .insert_sibling_after(&root, value)
+                                    .expect("the node is in the tree"),
+                                None => tree.append_child(value),
+                            };
+
+                            last_root = Some(id);
+
+                            id
+                        }
+                    };
+
+                    if let Some(head) = head {
+                        *head = Some(id);
+                    }
+                }
+            }
+        }
+
+        if let Some((line, _)) = lists.pop() {
+            return Err(ParseError::Syntax {
+                line,
+                reason: "unclosed parenthesis",
+            });
+        }
+
+        Ok(tree)
+    }
+
+    /// Writes the tree as S-expressions, that can be parsed with [`Tree::from_sexpr`].
+    pub fn to_sexpr<F>(&self, mut format: F) -> String
+    where
+        F: FnMut(&T) -> String,
+    {
+        let mut output = String::new();
+
+        let mut root = self.first_node;
+        while let Some(root_index) = root {
+            if root != self.first_node {
+                output.push('\n');
+            }
+
+            let mut current = Some(root_index);
+            while let Some(index) = current {
+                let node = self.nodes[index].unwrap_ref();
+
+                if index != root_index {
+                    output.push(' ');
+                }
+
+                if node.first_child.is_some() {
+                    output.push('(');
+                    write_atom(&mut output, &format(&node.value));
+                } else {
+                    write_atom(&mut output, &format(&node.value));
+
+                    // Close the lists of the ancestors this node is the last descendant of
+                    let mut last = index;
+                    while last != root_index {
+                        let node = self.nodes[last].unwrap_ref();
+                        if node.next_sibling.is_some() {
+                            break;
+                        }
+
+                        output.push(')');
+                        last = node.parent.expect("the node is in the subtree");
+                    }
+                }
+
+                current = self.next_in_subtree(root_index, index);
+            }
+
+            root = self.nodes[root_index].unwrap_ref().next_sibling;
+        }
+
+        output
+    }
+}
+
+fn write_atom(output: &mut String, atom: &str) {
+    let quote = atom.is_empty()
+        || atom
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '(' | ')' | '"' | '\\'));
+
+    if !quote {
+        output.push_str(atom);
+        return;
+    }
+
+    output.push('"');
+    for c in atom.chars() {
+        if matches!(c, '"' | '\\') {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+    output.push('"');
+}
+
+enum Token {
+    Open,
+    Close,
+    Atom(String),
+}
+
+struct Tokens<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            chars: input.chars().peekable(),
+            line: 1,
+        }
+    }
+
+    /// Returns the next token with the line it starts on.
+    fn next_token<E>(&mut self) -> Option<(usize, Result<Token, ParseError<E>>)> {
+        while let Some(c) = self.chars.next_if(|c| c.is_whitespace()) {
+            if c == '\n' {
+                self.line += 1;
+            }
+        }
+
+        let line = self.line;
+
+        let token = match self.chars.next()? {
+            '(' => Ok(Token::Open),
+            ')' => Ok(Token::Close),
+            '"' => self.quoted().map(Token::Atom),
+            c => {
+                let mut atom = String::from(c);
+                while let Some(c) = self
+                    .chars
+                    .next_if(|c| !c.is_whitespace() && !matches!(c, '(' | ')' | '"'))
+                {
+                    atom.push(c);
+                }
+
+                Ok(Token::Atom(atom))
+            }
+        };
+
+        Some((line, token))
+    }
+
+    fn quoted<E>(&mut self) -> Result<String, ParseError<E>> {
+        let line = self.line;
+        let unterminated = || ParseError::Syntax {
+            line,
+            reason: "unterminated string",
+        };
+
+        let mut atom = String::new();
+
+        loop {
+            let c = match self.chars.next().ok_or_else(unterminated)? {
+                '"' => return Ok(atom),
+                '\\' => self.chars.next().ok_or_else(unterminated)?,
+                c => c,
+            };
+
+            if c == '\n' {
+                self.line += 1;
+            }
+
+            atom.push(c);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{convert::Infallible, num::ParseIntError};
+
+    use crate::{error::ParseError, tree::Tree};
+    use pretty_assertions::assert_eq;
+
+    fn string(value: &str) -> Result<String, Infallible> {
+        Ok(value.to_string())
+    }
+
+    #[test]
+    fn should_parse_sexpr() {
+        let tree = Tree::from_sexpr("(a (b c) d)\ne", string).unwrap();
+
+        assert_eq!("a\n├── b\n│   └── c\n└── d\ne", tree.display().to_string());
+    }
+
+    #[test]
+    fn should_write_sexpr() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(1);
+        let child = tree.append_child(2);
+        tree.append_child(3);
+        tree.insert_sibling_after(&child, 4).unwrap();
+        tree.insert_sibling_after(&root, 5).unwrap();
+
+        assert_eq!("(1 (2 3) 4)\n5", tree.to_sexpr(ToString::to_string));
+    }
+
+    #[test]
+    fn should_round_trip_sexpr() {
+        let sexpr = "(a (b (c d e)) (f g))\n(h i)\nj";
+
+        let tree = Tree::from_sexpr(sexpr, string).unwrap();
+
+        assert_eq!(sexpr, tree.to_sexpr(Clone::clone));
+    }
+
+    #[test]
+    fn should_quote_atoms() {
+        let mut tree = Tree::new();
+
+        tree.append_child("with space".to_string());
+        tree.append_child("(\"quoted\")".to_string());
+        tree.append_child(String::new());
+
+        let sexpr = tree.to_sexpr(Clone::clone);
+        assert_eq!(r#"("with space" ("(\"quoted\")" ""))"#, sexpr);
+
+        let parsed = Tree::from_sexpr(&sexpr, string).unwrap();
+        assert_eq!(sexpr, parsed.to_sexpr(Clone::clone));
+    }
+
+    #[test]
+    fn should_reject_malformed_sexpr() {
+        let syntax = |input: &str| Tree::from_sexpr(input, string).map(|_| ());
+
+        assert_eq!(
+            Err(ParseError::Syntax {
+                line: 2,
+                reason: "unexpected closing parenthesis"
+            }),
+            syntax("(a b)\n)")
+        );
+        assert_eq!(
+            Err(ParseError::Syntax {
+                line: 1,
+                reason: "empty list"
+            }),
+            syntax("(a ())")
+        );
+        assert_eq!(
+            Err(ParseError::Syntax {
+                line: 1,
+                reason: "expected an atom"
+            }),
+            syntax("((a) b)")
+        );
+        assert_eq!(
+            Err(ParseError::Syntax {
+                line: 2,
+                reason: "unclosed parenthesis"
+            }),
+            syntax("a\n(b\nc")
+        );
+        assert_eq!(
+            Err(ParseError::Syntax {
+                line: 1,
+                reason: "unterminated string"
+            }),
+            syntax("(a \"b\n)")
+        );
+    }
+
+    #[test]
+    fn should_report_invalid_value() {
+        let parse = |value: &str| value.parse::<i32>();
+
+        let err: ParseError<ParseIntError> = Tree::from_sexpr("(1\n  (2 x))", parse).unwrap_err();
+
+        assert!(matches!(err, ParseError::Value { line: 2, .. }));
+    }
+}
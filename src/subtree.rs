@@ -0,0 +1,163 @@
+//! Reference to a node with all its descendants.
+
+use std::fmt::{self, Debug, Display};
+
+use crate::{display::DisplayTree, error::Error, id::NodeId, tree::Tree};
+
+/// A node of the tree with all its descendants.
+///
+/// It can be serialized with the `serde` feature, like a single node of the [`Tree`].
+pub struct SubtreeRef<'a, T> {
+    pub(crate) tree: &'a Tree<T>,
+    pub(crate) index: usize,
+}
+
+impl<T> Clone for SubtreeRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SubtreeRef<'_, T> {}
+
+impl<T> Tree<T> {
+    /// Returns a reference to the subtree of a node.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the [`NodeId`] is invalid.
+    pub fn subtree(&self, id: &NodeId) -> Result<SubtreeRef<'_, T>, Error> {
+        let index = self.try_index(id, "passed")?;
+
+        Ok(SubtreeRef { tree: self, index })
+    }
+}
+
+impl<'a, T> SubtreeRef<'a, T> {
+    /// Id of the root of the subtree.
+    #[must_use]
+    pub fn id(&self) -> NodeId {
+        self.tree.node_id(self.index)
+    }
+
+    /// Value of the root of the subtree.
+    #[must_use]
+    pub fn value(&self) -> &'a T {
+        &self.tree.nodes[self.index].unwrap_ref().value
+    }
+
+    /// Returns the subtrees of the children of the root.
+    pub fn children(&self) -> impl Iterator<Item = SubtreeRef<'a, T>> {
+        let tree = self.tree;
+        let mut child = tree.nodes[self.index].unwrap_ref().first_child;
+
+        std::iter::from_fn(move || {
+            let index = child?;
+
+            child = tree.nodes[index].unwrap_ref().next_sibling;
+
+            Some(SubtreeRef { tree, index })
+        })
+    }
+
+    /// Returns a wrapper to render the subtree with [`Display`], like [`Tree::display`].
+    #[must_use]
+    pub fn display(&self) -> DisplayTree<'a, T> {
+        DisplayTree::new(self.tree, Some(self.index))
+    }
+}
+
+struct ChildrenDebug<'a, T>(SubtreeRef<'a, T>);
+
+impl<T: Debug> Debug for ChildrenDebug<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.children()).finish()
+    }
+}
+
+impl<T: Debug> Debug for SubtreeRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Node")
+            .field("value", self.value())
+            .field("children", &ChildrenDebug(*self))
+            .finish()
+    }
+}
+
+/// Renders the subtree with the default options of [`SubtreeRef::display`].
+impl<T: Display> Display for SubtreeRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{display::DisplayStyle, error::Error, tree::Tree};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_display_subtree() {
+        let mut tree = Tree::new();
+        // 0 -> 1 -> 2
+        //        -> 3
+        //   -> 4
+        // 5
+        let root = tree.append_child(0);
+        let node = tree.append_child(1);
+        tree.append_child(2);
+        tree.append_child_to(&node, 3).unwrap();
+        tree.insert_sibling_after(&node, 4).unwrap();
+        tree.insert_sibling_after(&root, 5).unwrap();
+
+        let subtree = tree.subtree(&root).unwrap();
+
+        assert_eq!(root, subtree.id());
+        assert_eq!("0\n├── 1\n│   ├── 2\n│   └── 3\n└── 4", subtree.to_string());
+
+        // Doesn't continue to the siblings
+        let child = subtree.children().next().unwrap();
+        assert_eq!(
+            "1 #1v0\n|-- 2 #2v0\n`-- 3 #3v0",
+            child
+                .display()
+                .style(DisplayStyle::Ascii)
+                .show_ids(true)
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn should_debug_subtree() {
+        let mut tree = Tree::new();
+        // 0 -> 1 -> 2
+        //        -> 3
+        let root = tree.append_child(0);
+        let node = tree.append_child(1);
+        tree.append_child(2);
+        tree.append_child_to(&node, 3).unwrap();
+
+        let child = tree.subtree(&root).unwrap().children().next().unwrap();
+
+        assert_eq!(&1, child.value());
+        assert_eq!(
+            "Node { value: 1, children: [Node { value: 2, children: [] }, Node { value: 3, children: [] }] }",
+            format!("{:?}", child)
+        );
+    }
+
+    #[test]
+    fn should_not_get_removed_subtree() {
+        let mut tree = Tree::new();
+        // 0 -> 1
+        let root = tree.append_child(0);
+        tree.append_child(1);
+
+        tree.remove_subtree(root).unwrap();
+
+        assert_eq!(
+            Err(Error::Invalid("passed")),
+            tree.subtree(&root).map(|_| ())
+        );
+    }
+}
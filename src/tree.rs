@@ -1,7 +1,41 @@
-use crate::{entry::Entry, error::Error, id::NodeId, node::Node};
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{
+    entry::Entry,
+    error::Error,
+    id::{IdMap, NodeId},
+    node::Node,
+};
+
+/// Counter to assign a unique tag to each tree.
+static NEXT_TAG: AtomicU64 = AtomicU64::new(0);
+
+/// What happens to the children of a removed node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovePolicy {
+    /// The children take the place of the removed node between its siblings.
+    Splice,
+    /// The children become the last children of the parent of the removed node, or the last
+    /// top-level nodes if it has no parent.
+    Reparent,
+    /// The children are removed with all their descendants.
+    Subtree,
+    /// The children become the last top-level nodes of the tree.
+    Detach,
+}
 
-#[derive(Debug, Clone)]
+/// Arena based tree.
+///
+/// Each tree has a unique tag stored in the [`NodeId`]s it creates, so an id can't be used to
+/// access a different tree. A cloned tree gets a new tag, use [`Tree::clone_with_ids`] to map the
+/// ids of the original to the clone.
+#[derive(Debug)]
 pub struct Tree<T> {
+    pub(crate) tag: u64,
     pub(crate) first_free: Option<usize>,
     pub(crate) first_node: Option<usize>,
     pub(crate) last_node: Option<usize>,
@@ -19,6 +53,7 @@ impl<T> Tree<T> {
     #[must_use]
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
+            tag: Self::next_tag(),
             first_free: None,
             first_node: None,
             last_node: None,
@@ -26,17 +61,30 @@ impl<T> Tree<T> {
         }
     }
 
+    /// Returns a new unique tag for a tree.
+    ///
+    /// The counter is hashed with random keys, so the tag doesn't collide with the ones of trees
+    /// deserialized from a different process.
+    pub(crate) fn next_tag() -> u64 {
+        let count = NEXT_TAG.fetch_add(1, Ordering::Relaxed);
+
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(count);
+
+        hasher.finish()
+    }
+
     /// Add a node to the tree, without relations with the other nodes.
     pub fn create_node(&mut self, value: T) -> NodeId {
         let index = self.allocate_node(Node::new(value));
 
-        NodeId::new(index)
+        self.node_id(index)
     }
 
     /// Remove a node.
     ///
-    /// If there are some children nodes, they will became the first node if we are removing the
-    /// first node. Otherwise they will become orphan nodes.
+    /// If there are some children nodes, they will take the place of the removed node between its
+    /// siblings, like with [`RemovePolicy::Splice`].
     ///
     /// It will consume the [`NodeId`].
     ///
@@ -44,125 +92,248 @@ impl<T> Tree<T> {
     ///
     /// Fails if the [`NodeId`] is invalid.
     pub fn remove(&mut self, id: NodeId) -> Result<T, Error> {
-        let index = self.index(&id).ok_or(Error::Invalid("passed"))?;
-        let entry = self.free_node(index);
-
-        let node = entry.unwrap();
+        self.remove_with(id, RemovePolicy::Splice)
+    }
 
-        // Replace with next sibling or first child, there is no previous sibling
-        if Some(index) == self.first_node {
-            self.first_node = node.next_sibling.or(node.first_child);
+    /// Remove a node, choosing what happens to its children with the [`RemovePolicy`].
+    ///
+    /// It will consume the [`NodeId`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the [`NodeId`] is invalid.
+    pub fn remove_with(&mut self, id: NodeId, policy: RemovePolicy) -> Result<T, Error> {
+        if policy == RemovePolicy::Subtree {
+            let values = self.remove_subtree(id)?;
+
+            // The removed node is the first in depth-first order
+            return Ok(values
+                .into_iter()
+                .next()
+                .expect("the subtree contains the node"));
         }
 
-        // Replace with prev sibling or parent, there is no next sibling
-        if Some(index) == self.last_node {
-            self.last_node = node.prev_sibling.or(node.parent);
+        let index = self.try_index(&id, "passed")?;
+
+        let mut children = Vec::new();
+        let mut child_index = self.nodes[index].unwrap_ref().first_child;
+        while let Some(child) = child_index {
+            children.push(child);
+
+            child_index = self.nodes[child].unwrap_ref().next_sibling;
         }
 
-        // Check if this is a parent first/last child
-        if let Some(parent_index) = node.parent {
-            let parent = self.nodes[parent_index].unwrap_mut();
+        match policy {
+            RemovePolicy::Splice => {
+                for child in children {
+                    self.unlink(child);
 
-            if parent.first_child == Some(index) {
-                parent.first_child = node.next_sibling;
+                    let node = self.nodes[index].unwrap_ref();
+                    let parent = node.parent;
+                    let prev_sibling = node.prev_sibling;
+
+                    self.relate(child, parent, prev_sibling, Some(index));
+                }
+
+                self.unlink(index);
             }
+            RemovePolicy::Reparent => {
+                let parent = self.nodes[index].unwrap_ref().parent;
+
+                self.unlink(index);
 
-            if parent.last_child == Some(index) {
-                parent.last_child = node.prev_sibling;
+                for child in &children {
+                    self.unlink(*child);
+                }
+
+                match parent {
+                    Some(parent) => {
+                        for child in children {
+                            let last_child = self.nodes[parent].unwrap_ref().last_child;
+
+                            self.relate(child, Some(parent), last_child, None);
+                        }
+                    }
+                    None => self.append_roots(children),
+                }
             }
-        }
+            RemovePolicy::Detach => {
+                self.unlink(index);
 
-        // Connect next sibling with previous sibling
-        if let Some(index) = node.next_sibling {
-            let next_sibling = self.nodes[index].unwrap_mut();
-            next_sibling.prev_sibling = node.prev_sibling;
-        }
+                for child in &children {
+                    self.unlink(*child);
+                }
 
-        if let Some(index) = node.prev_sibling {
-            let prev_sibling = self.nodes[index].unwrap_mut();
-            prev_sibling.next_sibling = node.next_sibling;
+                self.append_roots(children);
+            }
+            RemovePolicy::Subtree => unreachable!("the subtree was already removed"),
         }
 
-        // Remove parent from child nodes
-        let mut child_index = node.first_child;
-        while let Some(index) = child_index {
-            let child = self.nodes[index].unwrap_mut();
-            child.parent = None;
+        Ok(self.free_node(index).unwrap().value)
+    }
 
-            child_index = child.next_sibling;
-        }
+    /// Remove a node with all its descendants.
+    ///
+    /// The values are returned in depth-first order, starting from the removed node.
+    ///
+    /// It will consume the [`NodeId`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the [`NodeId`] is invalid.
+    pub fn remove_subtree(&mut self, id: NodeId) -> Result<Vec<T>, Error> {
+        let index = self.try_index(&id, "passed")?;
+
+        self.unlink(index);
 
-        Ok(node.value)
+        let values = self
+            .subtree_indexes(index)
+            .into_iter()
+            .map(|index| self.free_node(index).unwrap().value)
+            .collect();
+
+        Ok(values)
     }
 
     #[must_use]
     pub fn first_node_id(&self) -> Option<NodeId> {
-        self.first_node.map(NodeId::new)
+        self.first_node.map(|index| self.node_id(index))
     }
 
     #[must_use]
     pub fn last_node_id(&self) -> Option<NodeId> {
-        self.last_node.map(NodeId::new)
+        self.last_node.map(|index| self.node_id(index))
     }
 
-    /// Insert the last child for a given index.
-    pub(crate) fn insert_child_at(&mut self, index: usize, value: T) -> usize {
-        let mut node = Node::new(value);
-        let node_index = self.get_first_free();
+    /// Moves a node with all its descendants into a new tree.
+    ///
+    /// The nodes are stored in depth-first order in the new tree, and the returned [`IdMap`] maps
+    /// the old ids to the ones in the new tree.
+    ///
+    /// It will consume the [`NodeId`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the [`NodeId`] is invalid.
+    pub fn split_off(&mut self, id: NodeId) -> Result<(Tree<T>, IdMap), Error> {
+        let index = self.try_index(&id, "passed")?;
 
-        node.parent = Some(index);
+        self.unlink(index);
 
-        if Some(index) == self.last_node {
-            self.last_node = Some(node_index);
-        }
+        let indexes = self.subtree_indexes(index);
+        let new_indexes: HashMap<usize, usize> = indexes
+            .iter()
+            .enumerate()
+            .map(|(new, old)| (*old, new))
+            .collect();
 
-        let parent = self.nodes[index].unwrap_mut();
+        let mut tree = Tree::with_capacity(indexes.len());
+        let mut map = IdMap::default();
 
-        let last_child = parent.last_child.replace(node_index);
+        for old in indexes {
+            let old_id = self.node_id(old);
 
-        match last_child {
-            Some(sibling_index) => {
-                let sibling = self.nodes[sibling_index].unwrap_mut();
-                sibling.next_sibling = Some(node_index);
-                node.prev_sibling = Some(sibling_index);
-            }
-            None => {
-                parent.first_child = Some(node_index);
-                parent.last_child = Some(node_index);
-            }
+            let mut node = self.free_node(old).unwrap();
+            node.map_indexes(|index| new_indexes[&index]);
+
+            let new = tree.allocate_node(node);
+
+            map.insert(old_id, tree.node_id(new));
         }
 
-        let index = self.allocate_node(node);
-        debug_assert_eq!(index, node_index);
+        tree.first_node = Some(0);
+        tree.last_node = Some(tree.last_descendant(0));
 
-        node_index
+        Ok((tree, map))
     }
 
-    /// Insert the next sibling for a given index.
-    pub(crate) fn insert_sibling_at(&mut self, index: usize, value: T) -> usize {
-        let node_index = self.get_first_free();
-        let mut node = Node::new(value);
+    /// Returns the last of the top-level nodes.
+    pub(crate) fn last_root(&self) -> Option<usize> {
+        let mut last = self.first_node?;
+
+        while let Some(next) = self.nodes[last].unwrap_ref().next_sibling {
+            last = next;
+        }
+
+        Some(last)
+    }
+
+    /// Relates the unlinked nodes, in order, as the last of the top-level nodes.
+    pub(crate) fn append_roots<I>(&mut self, indexes: I)
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        // Find the last root once, then link each node after the previous one
+        let mut last = self.last_root();
+
+        for index in indexes {
+            match last {
+                Some(last) => self.relate(index, None, Some(last), None),
+                None => {
+                    self.first_node = Some(index);
+                    self.last_node = Some(self.last_descendant(index));
+                }
+            }
+
+            last = Some(index);
+        }
+    }
 
-        node.prev_sibling = Some(index);
+    /// Returns the node after `index` in depth-first order, without leaving the subtree of `root`.
+    pub(crate) fn next_in_subtree(&self, root: usize, index: usize) -> Option<usize> {
+        let node = self.nodes[index].unwrap_ref();
 
-        let sibling = self.nodes[index].unwrap_mut();
-        let next_sibling = sibling.next_sibling.replace(node_index);
+        if node.first_child.is_some() {
+            return node.first_child;
+        }
 
-        node.next_sibling = next_sibling;
-        node.parent = sibling.parent;
+        let mut current = index;
+        while current != root {
+            let node = self.nodes[current].unwrap_ref();
 
-        if node.next_sibling.is_none() {
-            if let Some(parent) = node.parent {
-                self.nodes[parent].unwrap_mut().last_child = Some(node_index);
+            if node.next_sibling.is_some() {
+                return node.next_sibling;
             }
+
+            current = node.parent?;
         }
 
-        if Some(index) == self.last_node {
-            self.last_node = Some(node_index);
+        None
+    }
+
+    /// Returns the indexes of the subtree of a node in depth-first order.
+    pub(crate) fn subtree_indexes(&self, index: usize) -> Vec<usize> {
+        let mut indexes = vec![index];
+
+        let mut current = index;
+        while let Some(next) = self.next_in_subtree(index, current) {
+            indexes.push(next);
+            current = next;
         }
 
-        let index = self.allocate_node(node);
-        debug_assert_eq!(index, node_index);
+        indexes
+    }
+
+    /// Insert the last child for a given index.
+    pub(crate) fn insert_child_at(&mut self, index: usize, value: T) -> usize {
+        let node_index = self.allocate_node(Node::new(value));
+
+        let last_child = self.nodes[index].unwrap_ref().last_child;
+
+        self.relate(node_index, Some(index), last_child, None);
+
+        node_index
+    }
+
+    /// Insert the next sibling for a given index.
+    pub(crate) fn insert_sibling_at(&mut self, index: usize, value: T) -> usize {
+        let node_index = self.allocate_node(Node::new(value));
+
+        let sibling = self.nodes[index].unwrap_ref();
+        let parent = sibling.parent;
+        let next_sibling = sibling.next_sibling;
+
+        self.relate(node_index, parent, Some(index), next_sibling);
 
         node_index
     }
@@ -180,7 +351,7 @@ impl<T> Tree<T> {
             }
         };
 
-        NodeId::new(index)
+        self.node_id(index)
     }
 
     /// Appends the value to the last element of the three as its sibling. If None creates a new
@@ -197,7 +368,7 @@ impl<T> Tree<T> {
             }
         };
 
-        NodeId::new(index)
+        self.node_id(index)
     }
 
     /// Appends a new node as child of the given one
@@ -206,11 +377,11 @@ impl<T> Tree<T> {
     ///
     /// Will error if the given node id was removed.
     pub fn append_child_to(&mut self, id: &NodeId, value: T) -> Result<NodeId, Error> {
-        let index = self.index(id).ok_or(Error::Invalid("passed"))?;
+        let index = self.try_index(id, "passed")?;
 
         let index = self.insert_child_at(index, value);
 
-        Ok(NodeId::new(index))
+        Ok(self.node_id(index))
     }
 
     /// Insert a new node after the as the sibling of the given one
@@ -219,17 +390,82 @@ impl<T> Tree<T> {
     ///
     /// Will error if the given node id was removed.
     pub fn insert_sibling_after(&mut self, id: &NodeId, value: T) -> Result<NodeId, Error> {
-        let index = self.index(id).ok_or(Error::Invalid("passed"))?;
+        let index = self.try_index(id, "passed")?;
 
         let index = self.insert_sibling_at(index, value);
 
-        Ok(NodeId::new(index))
+        Ok(self.node_id(index))
+    }
+}
+
+impl<T: Clone> Tree<T> {
+    /// Copies a node with all its descendants, as the last child of `parent`.
+    ///
+    /// Returns the id of the copied node.
+    ///
+    /// # Errors
+    ///
+    /// - Fails if the `source` or the `parent` were removed
+    pub fn clone_subtree(&mut self, source: &NodeId, parent: &NodeId) -> Result<NodeId, Error> {
+        let source_index = self.try_index(source, "for source")?;
+        let parent_index = self.try_index(parent, "for parent")?;
+
+        // The copies are not included, even if the parent is in the subtree
+        let indexes = self.subtree_indexes(source_index);
+        let mut new_indexes = HashMap::with_capacity(indexes.len());
+
+        for index in indexes {
+            let node = self.nodes[index].unwrap_ref();
+            let value = node.value.clone();
+
+            let new_parent = if index == source_index {
+                parent_index
+            } else {
+                let old_parent = node.parent.expect("the node is a descendant of source");
+
+                new_indexes[&old_parent]
+            };
+
+            let new = self.insert_child_at(new_parent, value);
+            new_indexes.insert(index, new);
+        }
+
+        Ok(self.node_id(new_indexes[&source_index]))
+    }
+
+    /// Clones the tree, returning the map from the ids of the nodes to the ones in the clone.
+    #[must_use]
+    pub fn clone_with_ids(&self) -> (Self, IdMap) {
+        let tree = self.clone();
+
+        let mut map = IdMap::default();
+        for (index, entry) in self.nodes.iter().enumerate() {
+            if entry.is_node() {
+                map.insert(self.node_id(index), tree.node_id(index));
+            }
+        }
+
+        (tree, map)
+    }
+}
+
+impl<T: Clone> Clone for Tree<T> {
+    /// Clones the tree with a new tag, so the ids of the original are rejected by the clone.
+    fn clone(&self) -> Self {
+        Self {
+            tag: Self::next_tag(),
+            first_free: self.first_free,
+            first_node: self.first_node,
+            last_node: self.last_node,
+            nodes: self.nodes.clone(),
+        }
     }
 }
 
 impl<T> Default for Tree<T> {
     fn default() -> Self {
         Self {
+            tag: Self::next_tag(),
             first_free: Option::default(),
             first_node: Option::default(),
             last_node: Option::default(),
@@ -240,10 +476,10 @@ impl<T> Default for Tree<T> {
 
 #[cfg(test)]
 mod test {
-    use crate::{entry::Entry, node::Node};
+    use crate::{entry::Entry, error::Error, id::NodeId, node::Node};
     use pretty_assertions::assert_eq;
 
-    use super::Tree;
+    use super::{RemovePolicy, Tree};
 
     #[test]
     pub fn should_create_root_on_append_child() {
@@ -262,7 +498,13 @@ mod test {
             prev_sibling: None,
         };
 
-        assert_eq!(Entry::Occupied(node), tree.nodes[0]);
+        assert_eq!(
+            Entry::Occupied {
+                generation: 0,
+                node
+            },
+            tree.nodes[0]
+        );
     }
 
     #[test]
@@ -283,7 +525,13 @@ mod test {
             prev_sibling: None,
         };
 
-        assert_eq!(Entry::Occupied(first), tree.nodes[0]);
+        assert_eq!(
+            Entry::Occupied {
+                generation: 0,
+                node: first
+            },
+            tree.nodes[0]
+        );
 
         let second = Node {
             value: 2,
@@ -294,7 +542,13 @@ mod test {
             prev_sibling: None,
         };
 
-        assert_eq!(Entry::Occupied(second), tree.nodes[1]);
+        assert_eq!(
+            Entry::Occupied {
+                generation: 0,
+                node: second
+            },
+            tree.nodes[1]
+        );
     }
 
     #[test]
@@ -314,7 +568,13 @@ mod test {
             prev_sibling: None,
         };
 
-        assert_eq!(Entry::Occupied(node), tree.nodes[0]);
+        assert_eq!(
+            Entry::Occupied {
+                generation: 0,
+                node
+            },
+            tree.nodes[0]
+        );
     }
 
     #[test]
@@ -335,7 +595,13 @@ mod test {
             prev_sibling: None,
         };
 
-        assert_eq!(Entry::Occupied(first), tree.nodes[0]);
+        assert_eq!(
+            Entry::Occupied {
+                generation: 0,
+                node: first
+            },
+            tree.nodes[0]
+        );
 
         let second = Node {
             value: 2,
@@ -346,7 +612,13 @@ mod test {
             prev_sibling: Some(0),
         };
 
-        assert_eq!(Entry::Occupied(second), tree.nodes[1]);
+        assert_eq!(
+            Entry::Occupied {
+                generation: 0,
+                node: second
+            },
+            tree.nodes[1]
+        );
     }
 
     #[test]
@@ -366,7 +638,13 @@ mod test {
             next_sibling: None,
             prev_sibling: None,
         };
-        assert_eq!(Entry::Occupied(root), tree.nodes[0]);
+        assert_eq!(
+            Entry::Occupied {
+                generation: 0,
+                node: root
+            },
+            tree.nodes[0]
+        );
 
         let first = Node {
             value: 1,
@@ -376,7 +654,13 @@ mod test {
             next_sibling: Some(2),
             prev_sibling: None,
         };
-        assert_eq!(Entry::Occupied(first), tree.nodes[1]);
+        assert_eq!(
+            Entry::Occupied {
+                generation: 0,
+                node: first
+            },
+            tree.nodes[1]
+        );
 
         let second = Node {
             value: 2,
@@ -386,7 +670,13 @@ mod test {
             next_sibling: None,
             prev_sibling: Some(1),
         };
-        assert_eq!(Entry::Occupied(second), tree.nodes[2]);
+        assert_eq!(
+            Entry::Occupied {
+                generation: 0,
+                node: second
+            },
+            tree.nodes[2]
+        );
     }
 
     #[test]
@@ -405,7 +695,13 @@ mod test {
         assert_eq!(Some(1), tree.first_free);
         assert_eq!(Some(0), tree.first_node);
 
-        assert_eq!(Entry::Free { next_free: None }, tree.nodes[1]);
+        assert_eq!(
+            Entry::Free {
+                next_free: None,
+                generation: 1
+            },
+            tree.nodes[1]
+        );
 
         assert_eq!(None, tree.nodes[2].unwrap_ref().prev_sibling);
 
@@ -428,7 +724,13 @@ mod test {
         assert_eq!(Some(0), tree.first_free);
         assert_eq!(Some(1), tree.first_node);
 
-        assert_eq!(Entry::Free { next_free: None }, tree.nodes[0]);
+        assert_eq!(
+            Entry::Free {
+                next_free: None,
+                generation: 1
+            },
+            tree.nodes[0]
+        );
 
         assert_eq!(None, tree.nodes[1].unwrap_ref().parent);
         assert_eq!(None, tree.nodes[2].unwrap_ref().parent);
@@ -436,4 +738,305 @@ mod test {
         assert_eq!(2, *tree.get(&tree.first_node_id().unwrap()).unwrap());
         assert_eq!(4, *tree.get(&tree.last_node_id().unwrap()).unwrap());
     }
+
+    #[test]
+    fn should_invalidate_id_of_removed_node() {
+        let mut tree = Tree::new();
+
+        tree.append_child(1);
+        let removed = tree.append_child(2);
+
+        tree.remove(removed).unwrap();
+
+        let reused = tree.append_child(3);
+
+        assert_eq!(Some(&3), tree.get(&reused));
+        assert_eq!(None, tree.get(&removed));
+        assert_eq!(None, tree.get_mut(&removed));
+        assert_eq!(
+            Err(Error::Invalid("passed")),
+            tree.append_child_to(&removed, 4)
+        );
+        assert_eq!(Err(Error::Invalid("passed")), tree.remove(removed));
+    }
+
+    #[test]
+    fn should_reject_id_of_other_tree() {
+        let mut tree = Tree::new();
+        let mut other = Tree::new();
+
+        tree.append_child(1);
+        let id = other.append_child(2);
+
+        assert_eq!(None, tree.get(&id));
+        assert_eq!(None, tree.get_mut(&id));
+        assert_eq!(Err(Error::ForeignTree), tree.append_child_to(&id, 3));
+        assert_eq!(Err(Error::ForeignTree), tree.insert_sibling_after(&id, 3));
+        assert_eq!(Err(Error::ForeignTree), tree.remove(id));
+        assert!(tree.cursor(&id).is_none());
+
+        assert_eq!(Some(&2), other.get(&id));
+    }
+
+    #[test]
+    fn should_reject_id_of_clone() {
+        let mut tree = Tree::new();
+        let root = tree.append_child("root");
+
+        let mut clone = tree.clone();
+        let id = clone.append_child("only in clone");
+        tree.append_child("only in tree");
+
+        assert_eq!(None, tree.get(&id));
+        assert_eq!(None, clone.get(&root));
+        assert_eq!(Err(Error::ForeignTree), tree.append_child_to(&id, "child"));
+    }
+
+    #[test]
+    fn should_map_ids_to_clone() {
+        let mut tree = Tree::new();
+        let root = tree.append_child(1);
+        let removed = tree.append_child(2);
+        let child = tree.append_child(3);
+        tree.remove(removed).unwrap();
+
+        let (clone, ids) = tree.clone_with_ids();
+
+        assert_eq!(2, ids.len());
+        assert_eq!(None, ids.get(&removed));
+        assert_eq!(Some(&1), clone.get(&ids.get(&root).unwrap()));
+        assert_eq!(Some(&3), clone.get(&ids.get(&child).unwrap()));
+        assert_eq!(None, clone.get(&child));
+    }
+
+    #[test]
+    fn should_remove_subtree() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(1);
+        let first = tree.append_child(2);
+        tree.append_child(3);
+        tree.append_sibling(4);
+        let second = tree.insert_sibling_after(&first, 5).unwrap();
+
+        assert_eq!(Ok(vec![2, 3, 4]), tree.remove_subtree(first));
+
+        assert_eq!(vec![1, 5], tree.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(root), tree.first_node_id());
+        assert_eq!(Some(second), tree.last_node_id());
+        assert_eq!(None, tree.get(&first));
+
+        let free = tree.nodes.iter().filter(|entry| !entry.is_node()).count();
+        assert_eq!(3, free);
+
+        assert_eq!(Ok(vec![1, 5]), tree.remove_subtree(root));
+        assert_eq!(None, tree.first_node_id());
+        assert_eq!(None, tree.last_node_id());
+        assert_eq!(None, tree.iter().next());
+    }
+
+    /// Creates the tree used to test the [`RemovePolicy`]
+    ///
+    /// ```text
+    /// 0 -> 1 -> 2 -> 3
+    ///        -> 4
+    ///   -> 5
+    /// 6
+    /// ```
+    fn remove_policy_tree() -> (Tree<i32>, NodeId) {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(0);
+        let node = tree.append_child(1);
+        tree.append_child(2);
+        tree.append_child(3);
+        tree.append_child_to(&node, 4).unwrap();
+        tree.insert_sibling_after(&node, 5).unwrap();
+        tree.insert_sibling_after(&root, 6).unwrap();
+
+        (tree, node)
+    }
+
+    fn parents(tree: &Tree<i32>) -> Vec<(i32, Option<i32>)> {
+        tree.iter()
+            .map(|value| {
+                let index = tree
+                    .nodes
+                    .iter()
+                    .position(|entry| entry.map_ref(|node| node.value) == Some(*value))
+                    .unwrap();
+                let parent = tree.nodes[index]
+                    .unwrap_ref()
+                    .parent
+                    .map(|parent| tree.nodes[parent].unwrap_ref().value);
+
+                (*value, parent)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_remove_with_splice() {
+        let (mut tree, node) = remove_policy_tree();
+
+        assert_eq!(Ok(1), tree.remove_with(node, RemovePolicy::Splice));
+
+        assert_eq!(
+            vec![
+                (0, None),
+                (2, Some(0)),
+                (3, Some(2)),
+                (4, Some(0)),
+                (5, Some(0)),
+                (6, None)
+            ],
+            parents(&tree)
+        );
+        assert_eq!(Some(&6), tree.get(&tree.last_node_id().unwrap()));
+    }
+
+    #[test]
+    fn should_remove_with_reparent() {
+        let (mut tree, node) = remove_policy_tree();
+
+        assert_eq!(Ok(1), tree.remove_with(node, RemovePolicy::Reparent));
+
+        assert_eq!(
+            vec![
+                (0, None),
+                (5, Some(0)),
+                (2, Some(0)),
+                (3, Some(2)),
+                (4, Some(0)),
+                (6, None)
+            ],
+            parents(&tree)
+        );
+
+        let root = tree.first_node_id().unwrap();
+        assert_eq!(Ok(0), tree.remove_with(root, RemovePolicy::Reparent));
+
+        assert_eq!(
+            vec![(6, None), (5, None), (2, None), (3, Some(2)), (4, None)],
+            parents(&tree)
+        );
+        assert_eq!(Some(&4), tree.get(&tree.last_node_id().unwrap()));
+    }
+
+    #[test]
+    fn should_remove_with_subtree() {
+        let (mut tree, node) = remove_policy_tree();
+
+        assert_eq!(Ok(1), tree.remove_with(node, RemovePolicy::Subtree));
+
+        assert_eq!(vec![(0, None), (5, Some(0)), (6, None)], parents(&tree));
+        assert_eq!(
+            4,
+            tree.nodes.iter().filter(|entry| !entry.is_node()).count()
+        );
+    }
+
+    #[test]
+    fn should_remove_with_detach() {
+        let (mut tree, node) = remove_policy_tree();
+
+        assert_eq!(Ok(1), tree.remove_with(node, RemovePolicy::Detach));
+
+        assert_eq!(
+            vec![
+                (0, None),
+                (5, Some(0)),
+                (6, None),
+                (2, None),
+                (3, Some(2)),
+                (4, None)
+            ],
+            parents(&tree)
+        );
+        assert_eq!(Some(&4), tree.get(&tree.last_node_id().unwrap()));
+    }
+
+    #[test]
+    fn should_detach_many_children_between_many_roots() {
+        let mut tree = Tree::new();
+
+        // 0 with the children 1..10000, and the roots 10000..20000 after it
+        let node = tree.append_child(0);
+        for value in 1..10_000 {
+            tree.append_child_to(&node, value).unwrap();
+        }
+        let mut last = node;
+        for value in 10_000..20_000 {
+            last = tree.insert_sibling_after(&last, value).unwrap();
+        }
+
+        assert_eq!(Ok(0), tree.remove_with(node, RemovePolicy::Detach));
+
+        let mut values: Vec<i32> = (10_000..20_000).collect();
+        values.extend(1..10_000);
+
+        assert_eq!(values, tree.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(&9_999), tree.get(&tree.last_node_id().unwrap()));
+    }
+
+    #[test]
+    fn should_split_off() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(0);
+        let node = tree.append_child(1);
+        let child = tree.append_child(2);
+        tree.append_child(3);
+        tree.append_child_to(&node, 4).unwrap();
+        let sibling = tree.insert_sibling_after(&node, 5).unwrap();
+
+        let (split, map) = tree.split_off(node).unwrap();
+
+        assert_eq!(vec![0, 5], tree.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(root), tree.first_node_id());
+        assert_eq!(Some(sibling), tree.last_node_id());
+        assert_eq!(None, tree.get(&node));
+        assert_eq!(
+            4,
+            tree.nodes.iter().filter(|entry| !entry.is_node()).count()
+        );
+
+        assert_eq!(vec![1, 2, 3, 4], split.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(&4), split.get(&split.last_node_id().unwrap()));
+        assert_eq!(4, split.nodes.len());
+        assert_eq!(None, split.first_free);
+
+        assert_eq!(4, map.len());
+        assert_eq!(split.first_node_id(), map.get(&node));
+        assert_eq!(Some(&2), split.get(&map.get(&child).unwrap()));
+        assert_eq!(None, map.get(&root));
+    }
+
+    #[test]
+    fn should_clone_subtree() {
+        let mut tree = Tree::new();
+
+        let root = tree.append_child(0);
+        let node = tree.append_child(1);
+        tree.append_child(2);
+        tree.append_child_to(&node, 3).unwrap();
+        let sibling = tree.insert_sibling_after(&node, 4).unwrap();
+
+        let copy = tree.clone_subtree(&node, &sibling).unwrap();
+
+        assert_ne!(node, copy);
+        assert_eq!(Some(&1), tree.get(&copy));
+        assert_eq!(
+            vec![0, 1, 2, 3, 4, 1, 2, 3],
+            tree.iter().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&3), tree.get(&tree.last_node_id().unwrap()));
+
+        tree.clone_subtree(&root, &node).unwrap();
+
+        assert_eq!(
+            vec![0, 1, 2, 3, 0, 1, 2, 3, 4, 1, 2, 3, 4, 1, 2, 3],
+            tree.iter().copied().collect::<Vec<_>>()
+        );
+    }
 }